@@ -2,7 +2,6 @@ use proc_macro::TokenStream;
 use quote::quote;
 use syn::{
     parse::{Parse, ParseStream},
-    punctuated::Punctuated,
     DeriveInput,
 };
 
@@ -21,16 +20,26 @@ pub fn derive_relation(input: TokenStream) -> TokenStream {
             .into();
     };
 
-    let RelationAttributes { source, target } = match relation.parse_args::<RelationAttributes>() {
+    let RelationAttributes {
+        source,
+        target,
+        data,
+        reified,
+    } = match relation.parse_args::<RelationAttributes>() {
         Ok(attrs) => attrs,
         Err(err) => return err.to_compile_error().into(),
     };
 
+    let data = data.unwrap_or_else(|| syn::parse_quote!(()));
+    let reified = reified.then(|| quote! { const REIFIED: bool = true; });
+
     quote! {
         #[automatically_derived]
         impl ::evergreen_relations::relation::Relation for #ty {
             type Source = #source;
             type Target = #target;
+            type Data = #data;
+            #reified
         }
     }
     .into()
@@ -39,6 +48,8 @@ pub fn derive_relation(input: TokenStream) -> TokenStream {
 struct RelationAttributes {
     source: syn::Type,
     target: syn::Type,
+    data: Option<syn::Type>,
+    reified: bool,
 }
 
 impl Parse for RelationAttributes {
@@ -46,21 +57,36 @@ impl Parse for RelationAttributes {
         let span = input.span();
         let mut source = None;
         let mut target = None;
+        let mut data = None;
+        let mut reified = false;
+
+        while !input.is_empty() {
+            let name = input.parse::<syn::Ident>()?;
 
-        let fields: Punctuated<TypeField, syn::Token![,]> =
-            input.parse_terminated(Parse::parse, syn::Token![,])?;
+            if name == "reified" {
+                reified = true;
+            } else {
+                input.parse::<syn::Token![=]>()?;
+
+                match name.to_string().as_str() {
+                    "source" => source = Some(input.parse()?),
+                    "target" => target = Some(input.parse()?),
+                    "data" => data = Some(input.parse()?),
+                    _ => return Err(syn::Error::new_spanned(name, "unknown attribute")),
+                }
+            }
 
-        for field in fields {
-            match field.name.to_string().as_str() {
-                "source" => source = Some(field.ty),
-                "target" => target = Some(field.ty),
-                _ => return Err(syn::Error::new_spanned(field.name, "unknown attribute")),
+            if input.is_empty() {
+                break;
             }
+            input.parse::<syn::Token![,]>()?;
         }
 
         Ok(Self {
             source: source.ok_or_else(|| syn::Error::new(span, "missing `source` attribute"))?,
             target: target.ok_or_else(|| syn::Error::new(span, "missing `target` attribute"))?,
+            data,
+            reified,
         })
     }
 }
@@ -84,17 +110,51 @@ pub fn derive_relatable(input: TokenStream) -> TokenStream {
         container,
         relation,
         opposite,
+        on_despawn,
+        on_orphan,
+        on_dangling,
+        on_conflict,
     } = match relatable.parse_args::<RelatableAttributes>() {
         Ok(attrs) => attrs,
         Err(err) => return err.to_compile_error().into(),
     };
 
+    let on_despawn = on_despawn.map(|policy| {
+        quote! {
+            const ON_DESPAWN: ::evergreen_relations::relation::DespawnPolicy =
+                ::evergreen_relations::relation::DespawnPolicy::#policy;
+        }
+    });
+
+    let on_orphan = on_orphan.map(|policy| {
+        quote! {
+            const ON_ORPHAN: ::evergreen_relations::relation::OrphanPolicy = #policy;
+        }
+    });
+
+    let on_dangling = on_dangling.map(|policy| {
+        quote! {
+            const ON_DANGLING: ::evergreen_relations::relation::DanglingPolicy =
+                ::evergreen_relations::relation::DanglingPolicy::#policy;
+        }
+    });
+
+    let on_conflict = on_conflict.map(|policy| {
+        quote! {
+            const ON_CONFLICT: ::evergreen_relations::relation::ConflictPolicy = #policy;
+        }
+    });
+
     quote! {
         #[automatically_derived]
         impl ::evergreen_relations::relation::Relatable for #ty {
             type Relation = #relation;
             type Opposite = #opposite;
             type Container = #container;
+            #on_despawn
+            #on_orphan
+            #on_dangling
+            #on_conflict
         }
     }
     .into()
@@ -104,6 +164,10 @@ struct RelatableAttributes {
     container: syn::Type,
     relation: syn::Type,
     opposite: syn::Type,
+    on_despawn: Option<syn::Ident>,
+    on_orphan: Option<proc_macro2::TokenStream>,
+    on_dangling: Option<syn::Ident>,
+    on_conflict: Option<proc_macro2::TokenStream>,
 }
 
 impl Parse for RelatableAttributes {
@@ -115,15 +179,32 @@ impl Parse for RelatableAttributes {
         input.parse::<syn::Token![,]>()?;
 
         let mut opposite = None;
+        let mut on_despawn = None;
+        let mut on_orphan = None;
+        let mut on_dangling = None;
+        let mut on_conflict = None;
 
-        let fields: Punctuated<TypeField, syn::Token![,]> =
-            input.parse_terminated(Parse::parse, syn::Token![,])?;
+        while !input.is_empty() {
+            let name = input.parse::<syn::Ident>()?;
+            input.parse::<syn::Token![=]>()?;
 
-        for field in fields {
-            match field.name.to_string().as_str() {
-                "opposite" => opposite = Some(field.ty),
-                _ => return Err(syn::Error::new_spanned(field.name, "unknown attribute")),
+            match name.to_string().as_str() {
+                "opposite" => opposite = Some(input.parse()?),
+                "on_despawn" => {
+                    on_despawn = Some(parse_policy(input, &["cascade", "detach", "keep"])?)
+                }
+                "on_orphan" => on_orphan = Some(parse_orphan_policy(input)?),
+                "on_dangling" => {
+                    on_dangling = Some(parse_policy(input, &["prune", "warn", "report"])?)
+                }
+                "on_conflict" => on_conflict = Some(parse_conflict_policy(input)?),
+                _ => return Err(syn::Error::new_spanned(name, "unknown attribute")),
             }
+
+            if input.is_empty() {
+                break;
+            }
+            input.parse::<syn::Token![,]>()?;
         }
 
         let opposite =
@@ -133,20 +214,83 @@ impl Parse for RelatableAttributes {
             container,
             relation,
             opposite,
+            on_despawn,
+            on_orphan,
+            on_dangling,
+            on_conflict,
         })
     }
 }
 
-struct TypeField {
-    name: syn::Ident,
-    ty: syn::Type,
+/// Parses an orphan policy, which is either a plain policy name or
+/// `insert(<expr>)`, returning the `OrphanPolicy` expression.
+fn parse_orphan_policy(input: ParseStream) -> syn::Result<proc_macro2::TokenStream> {
+    if input.peek(syn::Ident) && input.peek2(syn::token::Paren) {
+        let name = input.parse::<syn::Ident>()?;
+        if name != "insert" {
+            return Err(syn::Error::new_spanned(name, "expected `insert(...)`"));
+        }
+
+        let content;
+        syn::parenthesized!(content in input);
+        let component = content.parse::<syn::Expr>()?;
+
+        return Ok(quote! {
+            ::evergreen_relations::relation::OrphanPolicy::Insert(|entity| {
+                entity.insert(#component);
+            })
+        });
+    }
+
+    let policy = parse_policy(input, &["keep", "despawn", "trigger"])?;
+    Ok(quote! { ::evergreen_relations::relation::OrphanPolicy::#policy })
 }
 
-impl Parse for TypeField {
-    fn parse(input: ParseStream) -> syn::Result<Self> {
-        let name = input.parse()?;
-        input.parse::<syn::Token![=]>()?;
-        let ty = input.parse()?;
-        Ok(Self { name, ty })
+/// Parses a conflict policy, which is either a plain policy name or
+/// `evict(<expr>)`, returning the `ConflictPolicy` expression.
+fn parse_conflict_policy(input: ParseStream) -> syn::Result<proc_macro2::TokenStream> {
+    if input.peek(syn::Ident) && input.peek2(syn::token::Paren) {
+        let name = input.parse::<syn::Ident>()?;
+        if name != "evict" {
+            return Err(syn::Error::new_spanned(name, "expected `evict(...)`"));
+        }
+
+        let content;
+        syn::parenthesized!(content in input);
+        let choose = content.parse::<syn::Expr>()?;
+
+        return Ok(quote! {
+            ::evergreen_relations::relation::ConflictPolicy::Evict(#choose)
+        });
     }
+
+    let policy = parse_policy(input, &["steal", "reject", "allow_asymmetric"])?;
+    Ok(quote! { ::evergreen_relations::relation::ConflictPolicy::#policy })
+}
+
+/// Parses a `snake_case` policy name, returning the matching `PascalCase`
+/// variant identifier.
+fn parse_policy(input: ParseStream, policies: &[&str]) -> syn::Result<syn::Ident> {
+    let policy = input.parse::<syn::Ident>()?;
+    let name = policy.to_string();
+
+    if !policies.contains(&name.as_str()) {
+        return Err(syn::Error::new_spanned(
+            policy,
+            format!("expected one of: {}", policies.join(", ")),
+        ));
+    }
+
+    let variant = name
+        .split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect::<String>();
+
+    Ok(syn::Ident::new(&variant, policy.span()))
 }
@@ -0,0 +1,192 @@
+use std::{
+    collections::VecDeque,
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, Weak,
+    },
+};
+
+use bevy_ecs::{
+    component::{ComponentId, Tick},
+    entity::Entity,
+    system::{ReadOnlySystemParam, Res, Resource, SystemChangeTick, SystemMeta, SystemParam},
+    world::{unsafe_world_cell::UnsafeWorldCell, World},
+};
+
+use crate::relation::Relation;
+
+/// [`Resource`] logging every edge of `R` that was added or removed, stamped
+/// with the change tick it happened at.
+///
+/// It is initialized by the first system using [`RelationChanges<R>`], and
+/// entries are dropped once every such system has seen them. Systems that
+/// were dropped no longer hold entries back.
+#[derive(Resource)]
+pub struct RelationChangeLog<R: Relation> {
+    entries: VecDeque<LogEntry>,
+    readers: Vec<Weak<AtomicU32>>,
+    marker: PhantomData<fn(R)>,
+}
+
+struct LogEntry {
+    tick: Tick,
+    added: bool,
+    source: Entity,
+    target: Entity,
+}
+
+impl<R: Relation> Default for RelationChangeLog<R> {
+    fn default() -> Self {
+        Self {
+            entries: VecDeque::new(),
+            readers: Vec::new(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<R: Relation> RelationChangeLog<R> {
+    /// Returns the number of logged changes that some reader hasn't seen yet.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if no changes are logged.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Logs an edge change, dropping the entries all readers have already seen.
+    pub(crate) fn push(&mut self, tick: Tick, added: bool, source: Entity, target: Entity) {
+        // Readers whose system was dropped are forgotten, so they don't hold entries back.
+        let mut seen = Vec::with_capacity(self.readers.len());
+        self.readers.retain(|reader| {
+            let reader = reader.upgrade();
+            if let Some(reader) = &reader {
+                seen.push(Tick::new(reader.load(Ordering::Relaxed)));
+            }
+            reader.is_some()
+        });
+
+        while self.entries.front().is_some_and(|entry| {
+            seen.iter()
+                .all(|&seen| !entry.tick.is_newer_than(seen, tick))
+        }) {
+            self.entries.pop_front();
+        }
+
+        self.entries.push_back(LogEntry {
+            tick,
+            added,
+            source,
+            target,
+        });
+    }
+}
+
+/// [`SystemParam`] giving the `(source, target)` edges of `R` that were added
+/// or removed since the system last ran.
+///
+/// Unlike [`RelationEvent`]s, this needs no setup, and any number of systems
+/// can read the same changes independently.
+///
+/// [`RelationEvent`]: crate::event::RelationEvent
+pub struct RelationChanges<'w, R: Relation> {
+    log: Res<'w, RelationChangeLog<R>>,
+    ticks: SystemChangeTick,
+}
+
+impl<R: Relation> RelationChanges<'_, R> {
+    /// Returns the edges that were added since the system last ran.
+    pub fn added(&self) -> impl Iterator<Item = (Entity, Entity)> + '_ {
+        self.changes(true)
+    }
+
+    /// Returns the edges that were removed since the system last ran.
+    pub fn removed(&self) -> impl Iterator<Item = (Entity, Entity)> + '_ {
+        self.changes(false)
+    }
+
+    /// Returns `true` if no edges were added or removed since the system last ran.
+    pub fn is_empty(&self) -> bool {
+        self.added().next().is_none() && self.removed().next().is_none()
+    }
+
+    fn changes(&self, added: bool) -> impl Iterator<Item = (Entity, Entity)> + '_ {
+        let (last_run, this_run) = (self.ticks.last_run(), self.ticks.this_run());
+        self.log
+            .entries
+            .iter()
+            .filter(move |entry| {
+                entry.added == added && entry.tick.is_newer_than(last_run, this_run)
+            })
+            .map(|entry| (entry.source, entry.target))
+    }
+}
+
+// SAFETY: Delegates to `Res` and `SystemChangeTick`, which register their own access.
+unsafe impl<R: Relation> SystemParam for RelationChanges<'_, R> {
+    type State = (ComponentId, Arc<AtomicU32>);
+    type Item<'w, 's> = RelationChanges<'w, R>;
+
+    fn init_state(world: &mut World, system_meta: &mut SystemMeta) -> Self::State {
+        let seen = Arc::new(AtomicU32::new(world.change_tick().get()));
+        world
+            .get_resource_or_insert_with(RelationChangeLog::<R>::default)
+            .readers
+            .push(Arc::downgrade(&seen));
+
+        let component_id =
+            <Res<RelationChangeLog<R>> as SystemParam>::init_state(world, system_meta);
+        (component_id, seen)
+    }
+
+    unsafe fn validate_param(
+        (component_id, _): &Self::State,
+        system_meta: &SystemMeta,
+        world: UnsafeWorldCell,
+    ) -> bool {
+        // SAFETY: Upheld by the caller.
+        unsafe {
+            <Res<RelationChangeLog<R>> as SystemParam>::validate_param(
+                component_id,
+                system_meta,
+                world,
+            )
+        }
+    }
+
+    unsafe fn get_param<'w, 's>(
+        (component_id, seen): &'s mut Self::State,
+        system_meta: &SystemMeta,
+        world: UnsafeWorldCell<'w>,
+        change_tick: Tick,
+    ) -> Self::Item<'w, 's> {
+        // SAFETY: Upheld by the caller.
+        let (log, ticks) = unsafe {
+            (
+                <Res<RelationChangeLog<R>> as SystemParam>::get_param(
+                    component_id,
+                    system_meta,
+                    world,
+                    change_tick,
+                ),
+                <SystemChangeTick as SystemParam>::get_param(
+                    &mut (),
+                    system_meta,
+                    world,
+                    change_tick,
+                ),
+            )
+        };
+
+        // Everything up to this run is seen by this system now, so it may be dropped from the log.
+        seen.store(change_tick.get(), Ordering::Relaxed);
+
+        RelationChanges { log, ticks }
+    }
+}
+
+// SAFETY: Only reads a resource and the change ticks.
+unsafe impl<R: Relation> ReadOnlySystemParam for RelationChanges<'_, R> {}
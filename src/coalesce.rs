@@ -0,0 +1,193 @@
+use std::{any::TypeId, marker::PhantomData, sync::Arc};
+
+use bevy_ecs::{entity::Entity, event::Event, event::Events, system::Resource, world::World};
+use bevy_utils::HashMap;
+
+use crate::{
+    event::{RelationCause, RelationEvent},
+    relation::Relation,
+};
+
+/// [`Resource`] that, when present, coalesces the [`RelationEvent`]s of `R`
+/// into net edge changes.
+///
+/// While it exists, relation changes are buffered here instead of being sent
+/// right away. [`flush_relation_events`] then sends a single event for every
+/// edge that actually changed since the previous call, so an add followed by a
+/// remove of the same pair cancels out.
+///
+/// Nothing is sent until [`flush_relation_events`] runs, which never happens
+/// on its own: changes are coalesced across any number of command flushes, so
+/// add it as a system where a batch should end, e.g. at the end of a schedule,
+/// or call it by hand.
+#[derive(Resource)]
+pub struct CoalescedRelationEvents<R: Relation> {
+    pending: Vec<PendingEdge<R::Data>>,
+    index: HashMap<(Entity, Entity), usize>,
+    marker: PhantomData<fn(R)>,
+}
+
+/// The net change of a single edge since the last flush.
+struct PendingEdge<D> {
+    source: Entity,
+    target: Entity,
+    /// Whether the edge existed before the first buffered change.
+    existed: bool,
+    /// Whether the edge exists after the last buffered change.
+    exists: bool,
+    cause: RelationCause,
+    /// The payload of the last buffered change.
+    data: Option<Arc<D>>,
+}
+
+impl<R: Relation> Default for CoalescedRelationEvents<R> {
+    fn default() -> Self {
+        Self {
+            pending: Vec::new(),
+            index: HashMap::default(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<R: Relation> CoalescedRelationEvents<R> {
+    /// Buffers the given event, merging it with earlier changes to the same edge.
+    pub(crate) fn push(&mut self, event: RelationEvent<R>) {
+        let (source, target, cause) = (event.source(), event.target(), event.cause());
+        let (added, data) = match event {
+            RelationEvent::Added { data, .. } => (true, data),
+            RelationEvent::Removed { data, .. } => (false, data),
+        };
+
+        // Both orientations are the same edge in an undirected relation.
+        let undirected = TypeId::of::<R::Source>() == TypeId::of::<R::Target>();
+        let key = if undirected && target < source {
+            (target, source)
+        } else {
+            (source, target)
+        };
+
+        match self.index.get(&key) {
+            Some(&i) => {
+                let edge = &mut self.pending[i];
+                edge.source = source;
+                edge.target = target;
+                edge.exists = added;
+                edge.cause = cause;
+                edge.data = data;
+            }
+            None => {
+                self.index.insert(key, self.pending.len());
+                self.pending.push(PendingEdge {
+                    source,
+                    target,
+                    existed: !added,
+                    exists: added,
+                    cause,
+                    data,
+                });
+            }
+        }
+    }
+
+    /// Returns `true` if no changes are buffered.
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Drains the buffered changes, returning the net events in the order the
+    /// edges were first changed.
+    pub fn drain(&mut self) -> impl Iterator<Item = RelationEvent<R>> + '_ {
+        self.index.clear();
+        self.pending
+            .drain(..)
+            .filter(|edge| edge.existed != edge.exists)
+            .map(|edge| {
+                if edge.exists {
+                    RelationEvent::added(edge.source, edge.target, edge.cause)
+                } else {
+                    RelationEvent::removed(edge.source, edge.target, edge.cause)
+                }
+                .with_data(edge.data)
+            })
+    }
+}
+
+/// An [`Event`] summarizing the net edge changes of `R` for one
+/// [`flush_relation_events`] call, as `(source, target)` pairs.
+#[derive(Event)]
+pub struct RelationBatch<R: Relation> {
+    pub added: Vec<(Entity, Entity)>,
+    pub removed: Vec<(Entity, Entity)>,
+    marker: PhantomData<fn(R)>,
+}
+
+impl<R: Relation> RelationBatch<R> {
+    pub fn new(added: Vec<(Entity, Entity)>, removed: Vec<(Entity, Entity)>) -> Self {
+        Self {
+            added,
+            removed,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<R: Relation> std::fmt::Debug for RelationBatch<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RelationBatch")
+            .field("added", &self.added)
+            .field("removed", &self.removed)
+            .finish()
+    }
+}
+
+impl<R: Relation> PartialEq for RelationBatch<R> {
+    fn eq(&self, other: &Self) -> bool {
+        self.added == other.added && self.removed == other.removed
+    }
+}
+
+impl<R: Relation> Eq for RelationBatch<R> {}
+
+impl<R: Relation> Clone for RelationBatch<R> {
+    fn clone(&self) -> Self {
+        Self::new(self.added.clone(), self.removed.clone())
+    }
+}
+
+/// Sends the net changes buffered in [`CoalescedRelationEvents<R>`] as
+/// [`RelationEvent`]s and a single [`RelationBatch`], for whichever of those
+/// event types are initialized.
+///
+/// Can be used as an exclusive system, e.g. at the end of a schedule.
+pub fn flush_relation_events<R: Relation>(world: &mut World) {
+    let Some(mut coalesced) = world.get_resource_mut::<CoalescedRelationEvents<R>>() else {
+        return;
+    };
+
+    if coalesced.is_empty() {
+        return;
+    }
+
+    let events = coalesced.drain().collect::<Vec<_>>();
+
+    if let Some(mut batches) = world.get_resource_mut::<Events<RelationBatch<R>>>() {
+        let (added, removed): (Vec<_>, Vec<_>) = events
+            .iter()
+            .partition(|event| matches!(event, RelationEvent::Added { .. }));
+        let pairs = |events: Vec<&RelationEvent<R>>| {
+            events
+                .into_iter()
+                .map(|event| (event.source(), event.target()))
+                .collect::<Vec<_>>()
+        };
+
+        if !added.is_empty() || !removed.is_empty() {
+            batches.send(RelationBatch::new(pairs(added), pairs(removed)));
+        }
+    }
+
+    if let Some(mut relation_events) = world.get_resource_mut::<Events<RelationEvent<R>>>() {
+        relation_events.send_batch(events);
+    }
+}
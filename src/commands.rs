@@ -0,0 +1,439 @@
+use bevy_ecs::{
+    entity::{Entity, EntityHashSet},
+    system::EntityCommands,
+    world::{EntityWorldMut, World},
+};
+
+use crate::{
+    container::EntityContainer,
+    error::{report_error, RelationError},
+    event::RelationCause,
+    related::{displaces, reconcile, unlink, Related, RelationData},
+    relation::{ConflictPolicy, Relatable},
+};
+
+/// Extension trait for relating entities without building [`Related`]
+/// components by hand.
+///
+/// The `N` type parameter picks the side of the relation that this entity is
+/// on. The opposite side is kept in sync.
+pub trait RelationCommands {
+    /// Relates this entity to `other`, keeping any existing related entities.
+    fn relate<N: Relatable>(&mut self, other: Entity) -> &mut Self;
+
+    /// Relates this entity to `other` with the given edge payload, replacing
+    /// the payload if they are already related.
+    fn relate_with<N: Relatable>(&mut self, other: Entity, data: RelationData<N>) -> &mut Self;
+
+    /// Removes the relation between this entity and `other`, if any.
+    fn unrelate<N: Relatable>(&mut self, other: Entity) -> &mut Self;
+
+    /// Removes every relation this entity has on the `N` side.
+    fn unrelate_all<N: Relatable>(&mut self) -> &mut Self;
+
+    /// Replaces the entities this entity is related to with the given ones.
+    fn set_related<N: Relatable>(
+        &mut self,
+        entities: impl IntoIterator<Item = Entity>,
+    ) -> &mut Self;
+
+    /// Mutates the [`Related`] component of this entity in place, if present.
+    ///
+    /// Unlike re-inserting the component, only the entities that were actually
+    /// added or removed by `f` are reconciled on the opposite side. The
+    /// component is removed if `f` leaves it empty.
+    fn modify_related<N: Relatable>(
+        &mut self,
+        f: impl FnOnce(&mut Related<N>) + Send + 'static,
+    ) -> &mut Self;
+}
+
+impl RelationCommands for EntityWorldMut<'_> {
+    fn relate<N: Relatable>(&mut self, other: Entity) -> &mut Self {
+        if refuse_placeholder::<N>(self, other) || refuse_unslotted::<N>(self, other) {
+            return self;
+        }
+
+        if !self.contains::<Related<N>>() {
+            return self.insert(Related::<N>::from(other));
+        }
+
+        match make_room::<N>(self, other) {
+            Room::Free => self.modify_related::<N>(move |related| related.push(other)),
+            Room::Evict(evicted) => {
+                evict::<N>(self, evicted);
+                self.modify_related::<N>(move |related| related.push(other))
+            }
+            Room::Refused => self,
+        }
+    }
+
+    fn relate_with<N: Relatable>(&mut self, other: Entity, data: RelationData<N>) -> &mut Self {
+        if refuse_placeholder::<N>(self, other) || refuse_unslotted::<N>(self, other) {
+            return self;
+        }
+
+        if !self.contains::<Related<N>>() {
+            return self.insert(Related::<N>::with_data(other, data));
+        }
+
+        match make_room::<N>(self, other) {
+            Room::Free => {
+                self.modify_related::<N>(move |related| related.push_with_data(other, data))
+            }
+            Room::Evict(evicted) => {
+                evict::<N>(self, evicted);
+                self.modify_related::<N>(move |related| related.push_with_data(other, data))
+            }
+            Room::Refused => self,
+        }
+    }
+
+    fn unrelate<N: Relatable>(&mut self, other: Entity) -> &mut Self {
+        self.modify_related::<N>(move |related| related.remove(other))
+    }
+
+    fn unrelate_all<N: Relatable>(&mut self) -> &mut Self {
+        self.remove::<Related<N>>()
+    }
+
+    fn set_related<N: Relatable>(
+        &mut self,
+        entities: impl IntoIterator<Item = Entity>,
+    ) -> &mut Self {
+        let entities = entities.into_iter();
+        let mut container = N::Container::with_capacity(entities.size_hint().0);
+        let mut seen = EntityHashSet::default();
+        let mut placeholder = false;
+        let entities = entities
+            .filter(|&id| {
+                placeholder |= id == Entity::PLACEHOLDER;
+                id != Entity::PLACEHOLDER && seen.insert(id)
+            })
+            .collect::<Vec<_>>();
+        container.extend(entities.as_slice().iter().copied());
+
+        if placeholder {
+            refuse_placeholder::<N>(self, Entity::PLACEHOLDER);
+        }
+
+        // Containers that need a slot refuse the entities instead.
+        for &id in &entities {
+            if !container.contains(id) {
+                refuse_unslotted::<N>(self, id);
+            }
+        }
+
+        if container.is_empty() {
+            return self.remove::<Related<N>>();
+        }
+
+        // Keep the payloads of the edges that remain. Their edge entities are
+        // restored from the other side.
+        let mut data = self
+            .get::<Related<N>>()
+            .map(|related| related.data.clone())
+            .unwrap_or_default();
+        data.retain(|&id, _| container.contains(id));
+
+        let mut related = Related::<N> {
+            container,
+            data,
+            edges: Default::default(),
+            removed: Default::default(),
+        };
+        related.imply_all_data();
+        self.insert(related)
+    }
+
+    fn modify_related<N: Relatable>(
+        &mut self,
+        f: impl FnOnce(&mut Related<N>) + Send + 'static,
+    ) -> &mut Self {
+        let a_id = self.id();
+        let Some(mut related) = self.get_mut::<Related<N>>() else {
+            return self;
+        };
+
+        let old = related.clone();
+        f(&mut related);
+
+        if related.is_empty() {
+            self.remove::<Related<N>>();
+        }
+
+        self.world_scope(|world| reconcile::<N>(world, a_id, &old));
+        self
+    }
+}
+
+impl RelationCommands for EntityCommands<'_> {
+    fn relate<N: Relatable>(&mut self, other: Entity) -> &mut Self {
+        self.queue(move |id: Entity, world: &mut World| {
+            with_entity::<N>(world, id, |entity| {
+                entity.relate::<N>(other);
+            });
+        })
+    }
+
+    fn relate_with<N: Relatable>(&mut self, other: Entity, data: RelationData<N>) -> &mut Self {
+        self.queue(move |id: Entity, world: &mut World| {
+            with_entity::<N>(world, id, |entity| {
+                entity.relate_with::<N>(other, data);
+            });
+        })
+    }
+
+    fn unrelate<N: Relatable>(&mut self, other: Entity) -> &mut Self {
+        self.queue(move |id: Entity, world: &mut World| {
+            with_entity::<N>(world, id, |entity| {
+                entity.unrelate::<N>(other);
+            });
+        })
+    }
+
+    fn unrelate_all<N: Relatable>(&mut self) -> &mut Self {
+        self.queue(move |id: Entity, world: &mut World| {
+            with_entity::<N>(world, id, |entity| {
+                entity.unrelate_all::<N>();
+            });
+        })
+    }
+
+    fn set_related<N: Relatable>(
+        &mut self,
+        entities: impl IntoIterator<Item = Entity>,
+    ) -> &mut Self {
+        let entities = entities.into_iter().collect::<Vec<_>>();
+        self.queue(move |id: Entity, world: &mut World| {
+            with_entity::<N>(world, id, |entity| {
+                entity.set_related::<N>(entities);
+            });
+        })
+    }
+
+    fn modify_related<N: Relatable>(
+        &mut self,
+        f: impl FnOnce(&mut Related<N>) + Send + 'static,
+    ) -> &mut Self {
+        self.queue(move |id: Entity, world: &mut World| {
+            with_entity::<N>(world, id, |entity| {
+                entity.modify_related::<N>(f);
+            });
+        })
+    }
+}
+
+/// Reports a [`RelationError::Placeholder`] if `other` is [`Entity::PLACEHOLDER`],
+/// returning `true` if so.
+fn refuse_placeholder<N: Relatable>(entity: &mut EntityWorldMut, other: Entity) -> bool {
+    if other != Entity::PLACEHOLDER {
+        return false;
+    }
+
+    let id = entity.id();
+    entity.world_scope(|world| report_error::<N>(world, RelationError::Placeholder(id)));
+    true
+}
+
+/// Reports a [`RelationError::NoSlot`] and returns `true` if the entity's
+/// container only takes entities in a slot.
+fn refuse_unslotted<N: Relatable>(entity: &mut EntityWorldMut, other: Entity) -> bool {
+    if !N::Container::SLOTTED {
+        return false;
+    }
+
+    let id = entity.id();
+    entity.world_scope(|world| {
+        report_error::<N>(
+            world,
+            RelationError::NoSlot {
+                entity: other,
+                target: id,
+            },
+        );
+    });
+    true
+}
+
+/// How an entity's own full container makes room for a new entity.
+enum Room {
+    /// The container has room.
+    Free,
+    /// The given entity must be removed first.
+    Evict(Entity),
+    /// The new entity doesn't fit.
+    Refused,
+}
+
+/// Applies the [`ConflictPolicy`] of `N` when relating this entity to `other`
+/// would displace an entity from its own container, reporting a
+/// [`RelationError::Rejected`] under [`ConflictPolicy::Reject`].
+fn make_room<N: Relatable>(entity: &mut EntityWorldMut, other: Entity) -> Room {
+    let id = entity.id();
+    let Some(displaced) = entity
+        .get::<Related<N>>()
+        .and_then(|related| related.container.displaced_by(other))
+    else {
+        return Room::Free;
+    };
+
+    match N::ON_CONFLICT {
+        ConflictPolicy::Steal => Room::Evict(displaced),
+        ConflictPolicy::Evict(choose) => {
+            let entities = entity
+                .get::<Related<N>>()
+                .map(|related| related.iter().collect::<Vec<_>>())
+                .unwrap_or_default();
+            let chosen = choose(entity.world(), id, &entities);
+
+            if entities.as_slice().contains(&chosen) {
+                Room::Evict(chosen)
+            } else {
+                Room::Evict(displaced)
+            }
+        }
+        ConflictPolicy::Reject => {
+            entity.world_scope(|world| {
+                report_error::<N>(
+                    world,
+                    RelationError::Rejected {
+                        entity: other,
+                        target: id,
+                    },
+                );
+            });
+            Room::Refused
+        }
+        ConflictPolicy::AllowAsymmetric => Room::Refused,
+    }
+}
+
+/// Removes `evicted` from the entity's own container to make room for a new
+/// entity, unlinking it with [`RelationCause::Steal`].
+fn evict<N: Relatable>(entity: &mut EntityWorldMut, evicted: Entity) {
+    let id = entity.id();
+    if let Some(mut related) = entity.get_mut::<Related<N>>() {
+        related.remove(evicted);
+    }
+
+    entity.world_scope(|world| unlink::<N>(world, id, evicted, RelationCause::Steal));
+}
+
+/// Runs `f` on the entity, reporting a [`RelationError::NoSuchEntity`] if it
+/// no longer exists.
+fn with_entity<N: Relatable>(world: &mut World, id: Entity, f: impl FnOnce(&mut EntityWorldMut)) {
+    match world.get_entity_mut(id) {
+        Ok(mut entity) => f(&mut entity),
+        Err(_) => report_error::<N>(world, RelationError::NoSuchEntity(id)),
+    }
+}
+
+/// Fallible variants of the [`RelationCommands`] on [`EntityWorldMut`], which
+/// check the operation up front instead of reporting errors from the hooks.
+pub trait TryRelationCommands {
+    /// Relates this entity to `other`, like [`RelationCommands::relate`].
+    ///
+    /// Fails if `other` is [`Entity::PLACEHOLDER`] or doesn't exist, or if
+    /// either entity would reject the other under [`ConflictPolicy::Reject`].
+    /// Nothing is changed on failure.
+    ///
+    /// [`ConflictPolicy::Reject`]: crate::relation::ConflictPolicy::Reject
+    fn try_relate<N: Relatable>(&mut self, other: Entity) -> Result<&mut Self, RelationError>;
+
+    /// Removes the relation between this entity and `other`, like
+    /// [`RelationCommands::unrelate`].
+    ///
+    /// Fails if the entities aren't related.
+    fn try_unrelate<N: Relatable>(&mut self, other: Entity) -> Result<&mut Self, RelationError>;
+
+    /// Replaces the entities this entity is related to, like
+    /// [`RelationCommands::set_related`].
+    ///
+    /// Fails if any of the entities would fail [`TryRelationCommands::try_relate`].
+    fn try_set_related<N: Relatable>(
+        &mut self,
+        entities: impl IntoIterator<Item = Entity>,
+    ) -> Result<&mut Self, RelationError>;
+}
+
+impl TryRelationCommands for EntityWorldMut<'_> {
+    fn try_relate<N: Relatable>(&mut self, other: Entity) -> Result<&mut Self, RelationError> {
+        check_relate::<N>(self, other)?;
+        Ok(self.relate::<N>(other))
+    }
+
+    fn try_unrelate<N: Relatable>(&mut self, other: Entity) -> Result<&mut Self, RelationError> {
+        if !self
+            .get::<Related<N>>()
+            .is_some_and(|related| related.contains(other))
+        {
+            return Err(RelationError::NotRelated {
+                entity: self.id(),
+                other,
+            });
+        }
+
+        Ok(self.unrelate::<N>(other))
+    }
+
+    fn try_set_related<N: Relatable>(
+        &mut self,
+        entities: impl IntoIterator<Item = Entity>,
+    ) -> Result<&mut Self, RelationError> {
+        let entities = entities.into_iter().collect::<Vec<_>>();
+        for &other in &entities {
+            check_relate::<N>(self, other)?;
+        }
+
+        Ok(self.set_related::<N>(entities))
+    }
+}
+
+fn check_relate<N: Relatable>(entity: &EntityWorldMut, other: Entity) -> Result<(), RelationError> {
+    let id = entity.id();
+    let world = entity.world();
+
+    if other == Entity::PLACEHOLDER {
+        return Err(RelationError::Placeholder(id));
+    }
+
+    if world.get_entity(other).is_err() {
+        return Err(RelationError::NoSuchEntity(other));
+    }
+
+    if <N::Opposite as Relatable>::Container::SLOTTED {
+        return Err(RelationError::NoSlot {
+            entity: id,
+            target: other,
+        });
+    }
+
+    if N::Container::SLOTTED {
+        return Err(RelationError::NoSlot {
+            entity: other,
+            target: id,
+        });
+    }
+
+    if matches!(N::Opposite::ON_CONFLICT, ConflictPolicy::Reject)
+        && displaces::<N>(world, id, other)
+    {
+        return Err(RelationError::Rejected {
+            entity: id,
+            target: other,
+        });
+    }
+
+    // This entity's own container may be full as well.
+    if matches!(N::ON_CONFLICT, ConflictPolicy::Reject)
+        && displaces::<N::Opposite>(world, other, id)
+    {
+        return Err(RelationError::Rejected {
+            entity: other,
+            target: id,
+        });
+    }
+
+    Ok(())
+}
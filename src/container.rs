@@ -1,6 +1,6 @@
-use std::fmt::Debug;
+use std::{any::Any, collections::BTreeSet, fmt::Debug};
 
-use bevy_ecs::entity::{Entity, EntityHashSet};
+use bevy_ecs::entity::{Entity, EntityHashMap, EntityHashSet};
 use smallvec::{smallvec, SmallVec};
 
 /// A container for the other entities that this entity is related to.
@@ -10,21 +10,115 @@ use smallvec::{smallvec, SmallVec};
 ///
 /// [`Related`]: crate::related::Related
 pub trait EntityContainer: Clone + PartialEq + Eq + Debug + Send + Sync + 'static {
+    /// Whether the order of the entities is meaningful, so that changing it
+    /// sends a [`RelationReordered`] event.
+    ///
+    /// [`RelationReordered`]: crate::event::RelationReordered
+    const ORDERED: bool = false;
+
+    /// Whether entities can only be added in a slot, e.g. with
+    /// [`NamedSlots::set`], so that [`push`] refuses them and relating entities
+    /// without a slot is reported as [`RelationError::NoSlot`].
+    ///
+    /// [`push`]: EntityContainer::push
+    /// [`RelationError::NoSlot`]: crate::error::RelationError::NoSlot
+    const SLOTTED: bool = false;
+
     /// Creates a new entity container with the initial given entity.
     fn new(entity: Entity) -> Self;
 
+    /// Creates an empty entity container.
+    fn empty() -> Self;
+
+    /// Creates an empty entity container with room for at least `capacity`
+    /// entities, for containers that can preallocate.
+    fn with_capacity(capacity: usize) -> Self {
+        let _ = capacity;
+        Self::empty()
+    }
+
     /// Returns `true` if this entity is not related to any other entities.
     fn is_empty(&self) -> bool;
 
+    /// Returns the number of entities this entity is related to.
+    fn len(&self) -> usize {
+        self.iter().count()
+    }
+
     /// Returns `true` if the given entity is related to this entity.
     fn contains(&self, entity: Entity) -> bool;
 
     /// Adds the given entity to the list of entities that this entity is related to.
+    ///
+    /// Pushing an entity that is already present has no effect, except for
+    /// containers that count the multiplicity of each entity.
     fn push(&mut self, entity: Entity);
 
     /// Removes the given entity from the list of entities that this entity is related to.
     fn remove(&mut self, entity: Entity);
 
+    /// Adds each of the given entities, like [`push`].
+    ///
+    /// [`push`]: EntityContainer::push
+    fn extend(&mut self, entities: impl IntoIterator<Item = Entity>) {
+        for entity in entities {
+            self.push(entity);
+        }
+    }
+
+    /// Keeps only the entities for which `f` returns `true`.
+    fn retain(&mut self, mut f: impl FnMut(Entity) -> bool) {
+        let removed = self.iter().filter(|&id| !f(id)).collect::<Vec<_>>();
+        for entity in removed {
+            self.remove(entity);
+        }
+    }
+
+    /// Removes all entities.
+    fn clear(&mut self) {
+        self.retain(|_| false);
+    }
+
+    /// Returns the entity that [`push`]ing the given entity would displace,
+    /// for containers that can only hold a limited number of entities.
+    ///
+    /// Returns `None` if the entity is already present.
+    ///
+    /// [`push`]: EntityContainer::push
+    fn displaced_by(&self, entity: Entity) -> Option<Entity> {
+        let _ = entity;
+        None
+    }
+
+    /// Returns the number of edges to the given entity, which is at most one
+    /// unless the container counts multiplicity.
+    fn multiplicity(&self, entity: Entity) -> usize {
+        usize::from(self.contains(entity))
+    }
+
+    /// Sets the number of edges to the given entity, for containers that count
+    /// multiplicity. Other containers only hold the entity once.
+    fn set_multiplicity(&mut self, entity: Entity, count: usize) {
+        if count == 0 {
+            self.remove(entity);
+        } else if !self.contains(entity) {
+            self.push(entity);
+        }
+    }
+
+    /// Returns the edge payload implied by where the given entity is stored,
+    /// for containers whose layout carries it, such as the slot of a
+    /// [`NamedSlots`] entry.
+    ///
+    /// Entities added without a payload of their own are given this one, if
+    /// it has the relation's [`Relation::Data`] type.
+    ///
+    /// [`Relation::Data`]: crate::relation::Relation::Data
+    fn implied_data(&self, entity: Entity) -> Option<Box<dyn Any>> {
+        let _ = entity;
+        None
+    }
+
     /// Consumes the entity container and returns an iterator over the entities
     /// that this entity is related to.
     fn into_iter(self) -> impl Iterator<Item = Entity>;
@@ -38,6 +132,10 @@ impl EntityContainer for Entity {
         entity
     }
 
+    fn empty() -> Self {
+        Entity::PLACEHOLDER
+    }
+
     fn is_empty(&self) -> bool {
         *self == Entity::PLACEHOLDER
     }
@@ -56,36 +154,124 @@ impl EntityContainer for Entity {
         }
     }
 
+    fn displaced_by(&self, entity: Entity) -> Option<Entity> {
+        (!self.is_empty() && *self != entity).then_some(*self)
+    }
+
+    fn into_iter(self) -> impl Iterator<Item = Entity> {
+        IntoIterator::into_iter(self.get())
+    }
+
+    fn iter(&self) -> impl Iterator<Item = Entity> {
+        IntoIterator::into_iter(self.get())
+    }
+}
+
+/// A container that holds at most a single entity.
+pub trait SingleEntityContainer: EntityContainer {
+    /// Returns the entity in this container, if any.
+    fn get(&self) -> Option<Entity>;
+}
+
+impl SingleEntityContainer for Entity {
+    fn get(&self) -> Option<Entity> {
+        (*self != Entity::PLACEHOLDER).then_some(*self)
+    }
+}
+
+impl SingleEntityContainer for Option<Entity> {
+    fn get(&self) -> Option<Entity> {
+        *self
+    }
+}
+
+/// A single-entity container, like [`Entity`], that is empty when `None`
+/// instead of holding [`Entity::PLACEHOLDER`].
+impl EntityContainer for Option<Entity> {
+    fn new(entity: Entity) -> Self {
+        Some(entity)
+    }
+
+    fn empty() -> Self {
+        None
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_none()
+    }
+
+    fn contains(&self, entity: Entity) -> bool {
+        *self == Some(entity)
+    }
+
+    fn push(&mut self, entity: Entity) {
+        *self = Some(entity);
+    }
+
+    fn remove(&mut self, entity: Entity) {
+        if *self == Some(entity) {
+            *self = None;
+        }
+    }
+
+    fn displaced_by(&self, entity: Entity) -> Option<Entity> {
+        self.filter(|&id| id != entity)
+    }
+
     fn into_iter(self) -> impl Iterator<Item = Entity> {
-        std::iter::once(self)
+        IntoIterator::into_iter(self)
     }
 
     fn iter(&self) -> impl Iterator<Item = Entity> {
-        std::iter::once(*self)
+        Option::iter(self).copied()
     }
 }
 
 impl<const N: usize> EntityContainer for SmallVec<[Entity; N]> {
+    const ORDERED: bool = true;
+
     fn new(entity: Entity) -> Self {
         smallvec![entity]
     }
 
+    fn empty() -> Self {
+        SmallVec::new()
+    }
+
+    fn with_capacity(capacity: usize) -> Self {
+        SmallVec::with_capacity(capacity)
+    }
+
     fn is_empty(&self) -> bool {
         self.is_empty()
     }
 
+    fn len(&self) -> usize {
+        self.len()
+    }
+
     fn contains(&self, entity: Entity) -> bool {
         self.as_slice().contains(&entity)
     }
 
     fn push(&mut self, entity: Entity) {
-        self.push(entity);
+        if !EntityContainer::contains(self, entity) {
+            self.push(entity);
+        }
     }
 
     fn remove(&mut self, entity: Entity) {
         self.retain(|&mut id| id != entity);
     }
 
+    fn retain(&mut self, mut f: impl FnMut(Entity) -> bool) {
+        self.retain(|&mut id| f(id));
+    }
+
+    fn clear(&mut self) {
+        self.clear();
+    }
+
     fn into_iter(self) -> impl Iterator<Item = Entity> {
         IntoIterator::into_iter(self)
     }
@@ -96,26 +282,50 @@ impl<const N: usize> EntityContainer for SmallVec<[Entity; N]> {
 }
 
 impl EntityContainer for Vec<Entity> {
+    const ORDERED: bool = true;
+
     fn new(entity: Entity) -> Self {
         vec![entity]
     }
 
+    fn empty() -> Self {
+        Vec::new()
+    }
+
+    fn with_capacity(capacity: usize) -> Self {
+        Vec::with_capacity(capacity)
+    }
+
     fn is_empty(&self) -> bool {
         self.is_empty()
     }
 
+    fn len(&self) -> usize {
+        self.len()
+    }
+
     fn contains(&self, entity: Entity) -> bool {
         self.as_slice().contains(&entity)
     }
 
     fn push(&mut self, entity: Entity) {
-        self.push(entity);
+        if !EntityContainer::contains(self, entity) {
+            self.push(entity);
+        }
     }
 
     fn remove(&mut self, entity: Entity) {
         self.retain(|&id| id != entity);
     }
 
+    fn retain(&mut self, mut f: impl FnMut(Entity) -> bool) {
+        self.retain(|&id| f(id));
+    }
+
+    fn clear(&mut self) {
+        self.clear();
+    }
+
     fn into_iter(self) -> impl Iterator<Item = Entity> {
         IntoIterator::into_iter(self)
     }
@@ -132,10 +342,68 @@ impl EntityContainer for EntityHashSet {
         set
     }
 
+    fn empty() -> Self {
+        EntityHashSet::default()
+    }
+
+    fn with_capacity(capacity: usize) -> Self {
+        EntityHashSet::with_capacity_and_hasher(capacity, Default::default())
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn contains(&self, entity: Entity) -> bool {
+        self.contains(&entity)
+    }
+
+    fn push(&mut self, entity: Entity) {
+        self.insert(entity);
+    }
+
+    fn remove(&mut self, entity: Entity) {
+        self.remove(&entity);
+    }
+
+    fn retain(&mut self, mut f: impl FnMut(Entity) -> bool) {
+        self.retain(|&id| f(id));
+    }
+
+    fn clear(&mut self) {
+        self.clear();
+    }
+
+    fn into_iter(self) -> impl Iterator<Item = Entity> {
+        IntoIterator::into_iter(self)
+    }
+
+    fn iter(&self) -> impl Iterator<Item = Entity> {
+        self.iter().copied()
+    }
+}
+
+impl EntityContainer for BTreeSet<Entity> {
+    fn new(entity: Entity) -> Self {
+        BTreeSet::from([entity])
+    }
+
+    fn empty() -> Self {
+        BTreeSet::new()
+    }
+
     fn is_empty(&self) -> bool {
         self.is_empty()
     }
 
+    fn len(&self) -> usize {
+        self.len()
+    }
+
     fn contains(&self, entity: Entity) -> bool {
         self.contains(&entity)
     }
@@ -148,6 +416,14 @@ impl EntityContainer for EntityHashSet {
         self.remove(&entity);
     }
 
+    fn retain(&mut self, mut f: impl FnMut(Entity) -> bool) {
+        self.retain(|&id| f(id));
+    }
+
+    fn clear(&mut self) {
+        self.clear();
+    }
+
     fn into_iter(self) -> impl Iterator<Item = Entity> {
         IntoIterator::into_iter(self)
     }
@@ -156,3 +432,734 @@ impl EntityContainer for EntityHashSet {
         self.iter().copied()
     }
 }
+
+/// An ordered container holding at most `CAP` entities, e.g. the items
+/// equipped in an inventory.
+///
+/// Pushing an entity into a full container evicts the oldest one. When another
+/// entity links to a full container, the [`ConflictPolicy`] of its side
+/// decides whether to evict the oldest entity, evict a chosen one, or reject
+/// the link.
+///
+/// `CAP` must be at least one, which is checked at compile time:
+///
+/// ```compile_fail
+/// # use evergreen_relations::container::BoundedEntities;
+/// let bounded = BoundedEntities::<0>::default();
+/// ```
+///
+/// [`ConflictPolicy`]: crate::relation::ConflictPolicy
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BoundedEntities<const CAP: usize>(SmallVec<[Entity; CAP]>);
+
+impl<const CAP: usize> BoundedEntities<CAP> {
+    /// Returns `true` if the container holds `CAP` entities.
+    pub fn is_full(&self) -> bool {
+        self.0.len() >= CAP
+    }
+}
+
+impl<const CAP: usize> Default for BoundedEntities<CAP> {
+    fn default() -> Self {
+        const { assert!(CAP > 0, "BoundedEntities must hold at least one entity") };
+        Self(SmallVec::new())
+    }
+}
+
+impl<const CAP: usize> EntityContainer for BoundedEntities<CAP> {
+    const ORDERED: bool = true;
+
+    fn new(entity: Entity) -> Self {
+        let mut bounded = Self::default();
+        bounded.0.push(entity);
+        bounded
+    }
+
+    fn empty() -> Self {
+        Self::default()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn contains(&self, entity: Entity) -> bool {
+        self.0.as_slice().contains(&entity)
+    }
+
+    fn push(&mut self, entity: Entity) {
+        if self.contains(entity) {
+            return;
+        }
+
+        if self.is_full() {
+            self.0.remove(0);
+        }
+        self.0.push(entity);
+    }
+
+    fn remove(&mut self, entity: Entity) {
+        self.0.retain(|&mut id| id != entity);
+    }
+
+    fn retain(&mut self, mut f: impl FnMut(Entity) -> bool) {
+        self.0.retain(|&mut id| f(id));
+    }
+
+    fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    fn displaced_by(&self, entity: Entity) -> Option<Entity> {
+        if self.is_full() && !self.contains(entity) {
+            self.0.first().copied()
+        } else {
+            None
+        }
+    }
+
+    fn into_iter(self) -> impl Iterator<Item = Entity> {
+        IntoIterator::into_iter(self.0)
+    }
+
+    fn iter(&self) -> impl Iterator<Item = Entity> {
+        self.0.as_slice().iter().copied()
+    }
+}
+
+impl<const CAP: usize> FromIterator<Entity> for BoundedEntities<CAP> {
+    fn from_iter<T: IntoIterator<Item = Entity>>(iter: T) -> Self {
+        let mut bounded = Self::default();
+        bounded.extend(iter);
+        bounded
+    }
+}
+
+/// An ordered container whose entities can be rearranged, e.g. the children
+/// of a UI node.
+///
+/// Rearranging the entities through [`RelationCommands::modify_related`] sends
+/// a [`RelationReordered`] event instead of removing and re-adding them.
+///
+/// [`RelationCommands::modify_related`]: crate::commands::RelationCommands::modify_related
+/// [`RelationReordered`]: crate::event::RelationReordered
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OrderedEntities(Vec<Entity>);
+
+impl OrderedEntities {
+    /// Returns the index of the given entity, if present.
+    pub fn index_of(&self, entity: Entity) -> Option<usize> {
+        self.0.as_slice().iter().position(|&id| id == entity)
+    }
+
+    /// Returns the entity at the given index, if any.
+    pub fn at(&self, index: usize) -> Option<Entity> {
+        self.0.get(index).copied()
+    }
+
+    /// Inserts the given entity at `index`, or moves it there if it is
+    /// already present.
+    ///
+    /// The index is clamped to the number of entities.
+    pub fn insert_at(&mut self, index: usize, entity: Entity) {
+        if let Some(old) = self.index_of(entity) {
+            self.0.remove(old);
+        }
+
+        let index = index.min(self.0.len());
+        self.0.insert(index, entity);
+    }
+
+    /// Moves the given entity to `index`, clamped to the last index.
+    ///
+    /// Returns `false` if the entity isn't present.
+    pub fn move_to(&mut self, entity: Entity, index: usize) -> bool {
+        let Some(old) = self.index_of(entity) else {
+            return false;
+        };
+
+        self.0.remove(old);
+        let index = index.min(self.0.len());
+        self.0.insert(index, entity);
+        true
+    }
+
+    /// Swaps the positions of the two given entities.
+    ///
+    /// Returns `false` if either entity isn't present.
+    pub fn swap(&mut self, a: Entity, b: Entity) -> bool {
+        let (Some(a), Some(b)) = (self.index_of(a), self.index_of(b)) else {
+            return false;
+        };
+
+        self.0.swap(a, b);
+        true
+    }
+
+    /// Sorts the entities by the key returned by `f`, keeping the order of
+    /// entities with equal keys.
+    pub fn sort_by_key<K: Ord>(&mut self, f: impl FnMut(&Entity) -> K) {
+        self.0.sort_by_key(f);
+    }
+}
+
+impl EntityContainer for OrderedEntities {
+    const ORDERED: bool = true;
+
+    fn new(entity: Entity) -> Self {
+        Self(vec![entity])
+    }
+
+    fn empty() -> Self {
+        Self::default()
+    }
+
+    fn with_capacity(capacity: usize) -> Self {
+        Self(Vec::with_capacity(capacity))
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn contains(&self, entity: Entity) -> bool {
+        self.0.as_slice().contains(&entity)
+    }
+
+    fn push(&mut self, entity: Entity) {
+        if !self.contains(entity) {
+            self.0.push(entity);
+        }
+    }
+
+    fn remove(&mut self, entity: Entity) {
+        self.0.retain(|&id| id != entity);
+    }
+
+    fn retain(&mut self, mut f: impl FnMut(Entity) -> bool) {
+        self.0.retain(|&id| f(id));
+    }
+
+    fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    fn into_iter(self) -> impl Iterator<Item = Entity> {
+        IntoIterator::into_iter(self.0)
+    }
+
+    fn iter(&self) -> impl Iterator<Item = Entity> {
+        self.0.as_slice().iter().copied()
+    }
+}
+
+impl From<Vec<Entity>> for OrderedEntities {
+    fn from(entities: Vec<Entity>) -> Self {
+        let mut ordered = Self::default();
+        ordered.extend(entities);
+        ordered
+    }
+}
+
+impl FromIterator<Entity> for OrderedEntities {
+    fn from_iter<T: IntoIterator<Item = Entity>>(iter: T) -> Self {
+        Self::from(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+/// An insertion-ordered set of entities with constant-time [`contains`] and
+/// [`remove`], suited for entities with very many related entities.
+///
+/// Removed entities leave a tombstone behind, and the tombstones are compacted
+/// away once they outnumber the entities.
+///
+/// [`contains`]: EntityContainer::contains
+/// [`remove`]: EntityContainer::remove
+#[derive(Clone, Default)]
+pub struct IndexedEntities {
+    entries: Vec<Option<Entity>>,
+    index: EntityHashMap<usize>,
+}
+
+impl IndexedEntities {
+    fn compact(&mut self) {
+        self.entries.retain(Option::is_some);
+        for (i, entity) in self.entries.iter().enumerate() {
+            if let Some(entity) = entity {
+                self.index.insert(*entity, i);
+            }
+        }
+    }
+}
+
+impl EntityContainer for IndexedEntities {
+    const ORDERED: bool = true;
+
+    fn new(entity: Entity) -> Self {
+        let mut indexed = Self::default();
+        indexed.push(entity);
+        indexed
+    }
+
+    fn empty() -> Self {
+        Self::default()
+    }
+
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+            index: EntityHashMap::with_capacity_and_hasher(capacity, Default::default()),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    fn contains(&self, entity: Entity) -> bool {
+        self.index.contains_key(&entity)
+    }
+
+    fn push(&mut self, entity: Entity) {
+        if self.contains(entity) {
+            return;
+        }
+
+        self.index.insert(entity, self.entries.len());
+        self.entries.push(Some(entity));
+    }
+
+    fn remove(&mut self, entity: Entity) {
+        let Some(i) = self.index.remove(&entity) else {
+            return;
+        };
+
+        self.entries[i] = None;
+        if self.index.is_empty() {
+            self.entries.clear();
+        } else if self.entries.len() > 2 * self.index.len() {
+            self.compact();
+        }
+    }
+
+    fn retain(&mut self, mut f: impl FnMut(Entity) -> bool) {
+        self.index.clear();
+        self.entries.retain(|entry| entry.is_some_and(&mut f));
+        for (i, entity) in self.entries.iter().flatten().enumerate() {
+            self.index.insert(*entity, i);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.index.clear();
+    }
+
+    fn into_iter(self) -> impl Iterator<Item = Entity> {
+        self.entries.into_iter().flatten()
+    }
+
+    fn iter(&self) -> impl Iterator<Item = Entity> {
+        self.entries.iter().flatten().copied()
+    }
+}
+
+impl PartialEq for IndexedEntities {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl Eq for IndexedEntities {}
+
+impl Debug for IndexedEntities {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(EntityContainer::iter(self)).finish()
+    }
+}
+
+impl FromIterator<Entity> for IndexedEntities {
+    fn from_iter<T: IntoIterator<Item = Entity>>(iter: T) -> Self {
+        let mut indexed = Self::default();
+        for entity in iter {
+            indexed.push(entity);
+        }
+        indexed
+    }
+}
+
+/// A container of named slots, each holding at most one entity, e.g. the head
+/// and hands of an equipment loadout.
+///
+/// Slots are keyed by a user enum or a `&'static str`. Entities are only added
+/// in a slot, with [`NamedSlots::set`] or [`Related::set_slot`], so pushing one
+/// without a slot does nothing, and relating entities without a slot, e.g. from
+/// the other side of the relation, is reported as [`RelationError::NoSlot`].
+/// Only [`EntityContainer::new`] puts its entity in the [`Default`] slot.
+///
+/// When the relation's payload is the slot key, each entity is given its slot
+/// as payload.
+///
+/// [`Related::set_slot`]: crate::related::Related::set_slot
+/// [`RelationError::NoSlot`]: crate::error::RelationError::NoSlot
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NamedSlots<K>(Vec<(K, Entity)>);
+
+impl<K> Default for NamedSlots<K> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+impl<K: SlotKey> NamedSlots<K> {
+    /// Returns the entity in the given slot, if any.
+    pub fn get(&self, slot: K) -> Option<Entity> {
+        self.0
+            .iter()
+            .find(|(key, _)| *key == slot)
+            .map(|&(_, entity)| entity)
+    }
+
+    /// Returns the slot the given entity occupies, if any.
+    pub fn slot_of(&self, entity: Entity) -> Option<K> {
+        self.0
+            .iter()
+            .find(|(_, id)| *id == entity)
+            .map(|&(key, _)| key)
+    }
+
+    /// Puts the given entity in the given slot, moving it out of any other
+    /// slot, and returns the entity it displaced, if any.
+    pub fn set(&mut self, slot: K, entity: Entity) -> Option<Entity> {
+        self.0.retain(|&(_, id)| id != entity);
+
+        match self.0.iter_mut().find(|(key, _)| *key == slot) {
+            Some((_, occupant)) => Some(std::mem::replace(occupant, entity)),
+            None => {
+                self.0.push((slot, entity));
+                None
+            }
+        }
+    }
+
+    /// Empties the given slot, returning the entity it held, if any.
+    pub fn clear(&mut self, slot: K) -> Option<Entity> {
+        let i = self.0.iter().position(|(key, _)| *key == slot)?;
+        Some(self.0.remove(i).1)
+    }
+
+    /// Returns an iterator over the occupied slots and their entities.
+    pub fn iter_slots(&self) -> impl Iterator<Item = (K, Entity)> + '_ {
+        self.0.iter().copied()
+    }
+}
+
+/// Trait for the keys of [`NamedSlots`], implemented for any small, comparable
+/// type such as a fieldless enum or a `&'static str`.
+pub trait SlotKey: Copy + Eq + Default + Debug + Send + Sync + 'static {}
+
+impl<K: Copy + Eq + Default + Debug + Send + Sync + 'static> SlotKey for K {}
+
+impl<K: SlotKey> EntityContainer for NamedSlots<K> {
+    const SLOTTED: bool = true;
+
+    fn new(entity: Entity) -> Self {
+        Self(vec![(K::default(), entity)])
+    }
+
+    fn empty() -> Self {
+        Self::default()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn contains(&self, entity: Entity) -> bool {
+        self.0.iter().any(|&(_, id)| id == entity)
+    }
+
+    fn push(&mut self, entity: Entity) {
+        // Entities can only be added in a slot.
+        let _ = entity;
+    }
+
+    fn remove(&mut self, entity: Entity) {
+        self.0.retain(|&(_, id)| id != entity);
+    }
+
+    fn retain(&mut self, mut f: impl FnMut(Entity) -> bool) {
+        self.0.retain(|&(_, id)| f(id));
+    }
+
+    fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    fn implied_data(&self, entity: Entity) -> Option<Box<dyn Any>> {
+        self.slot_of(entity)
+            .map(|slot| Box::new(slot) as Box<dyn Any>)
+    }
+
+    fn into_iter(self) -> impl Iterator<Item = Entity> {
+        IntoIterator::into_iter(self.0).map(|(_, entity)| entity)
+    }
+
+    fn iter(&self) -> impl Iterator<Item = Entity> {
+        self.0.iter().map(|&(_, entity)| entity)
+    }
+}
+
+/// A multigraph container that counts the number of edges to each entity,
+/// e.g. how many favors one entity owes another.
+///
+/// Each entity is iterated once, in insertion order, regardless of its
+/// multiplicity. The multiplicity is mirrored on the other side of the
+/// relation when changed through [`RelationCommands::modify_related`].
+///
+/// [`RelationCommands::modify_related`]: crate::commands::RelationCommands::modify_related
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MultiEntities(Vec<(Entity, usize)>);
+
+impl MultiEntities {
+    /// Adds an edge to the given entity, returning its new multiplicity.
+    pub fn add_edge(&mut self, entity: Entity) -> usize {
+        match self.0.iter_mut().find(|(id, _)| *id == entity) {
+            Some((_, count)) => {
+                *count += 1;
+                *count
+            }
+            None => {
+                self.0.push((entity, 1));
+                1
+            }
+        }
+    }
+
+    /// Removes a single edge to the given entity, removing the entity once it
+    /// has none left, and returns its remaining multiplicity.
+    pub fn remove_one_edge(&mut self, entity: Entity) -> usize {
+        let Some(i) = self.0.iter().position(|(id, _)| *id == entity) else {
+            return 0;
+        };
+
+        self.0[i].1 -= 1;
+        let count = self.0[i].1;
+        if count == 0 {
+            self.0.remove(i);
+        }
+        count
+    }
+}
+
+impl EntityContainer for MultiEntities {
+    fn new(entity: Entity) -> Self {
+        Self(vec![(entity, 1)])
+    }
+
+    fn empty() -> Self {
+        Self::default()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn contains(&self, entity: Entity) -> bool {
+        self.0.iter().any(|&(id, _)| id == entity)
+    }
+
+    fn push(&mut self, entity: Entity) {
+        self.add_edge(entity);
+    }
+
+    fn remove(&mut self, entity: Entity) {
+        self.0.retain(|&(id, _)| id != entity);
+    }
+
+    fn retain(&mut self, mut f: impl FnMut(Entity) -> bool) {
+        self.0.retain(|&(id, _)| f(id));
+    }
+
+    fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    fn multiplicity(&self, entity: Entity) -> usize {
+        self.0
+            .iter()
+            .find(|(id, _)| *id == entity)
+            .map_or(0, |&(_, count)| count)
+    }
+
+    fn set_multiplicity(&mut self, entity: Entity, count: usize) {
+        if count == 0 {
+            return self.remove(entity);
+        }
+
+        match self.0.iter_mut().find(|(id, _)| *id == entity) {
+            Some((_, old)) => *old = count,
+            None => self.0.push((entity, count)),
+        }
+    }
+
+    fn into_iter(self) -> impl Iterator<Item = Entity> {
+        IntoIterator::into_iter(self.0).map(|(entity, _)| entity)
+    }
+
+    fn iter(&self) -> impl Iterator<Item = Entity> {
+        self.0.iter().map(|&(entity, _)| entity)
+    }
+}
+
+/// A dense bitset container keyed by entity index, for entities related to
+/// tens of thousands of others, e.g. a faction containing every unit.
+///
+/// [`contains`] is constant-time and iteration walks the bitset in index
+/// order. The generation of each entity is stored alongside its bit, so an
+/// entity that reuses the index of a despawned one isn't reported as present.
+///
+/// Memory grows with the highest entity index held, so this is best suited
+/// for relations with a high fan-out.
+///
+/// [`contains`]: EntityContainer::contains
+#[derive(Clone, Default)]
+pub struct EntityBitSet {
+    bits: Vec<u64>,
+    /// The high bits of each entity, holding its generation, by index.
+    generations: Vec<u32>,
+    len: usize,
+}
+
+impl EntityBitSet {
+    fn split(entity: Entity) -> (usize, u32) {
+        let bits = entity.to_bits();
+        (entity.index() as usize, (bits >> 32) as u32)
+    }
+
+    fn is_set(&self, index: usize) -> bool {
+        self.bits
+            .get(index / 64)
+            .is_some_and(|word| word & (1 << (index % 64)) != 0)
+    }
+}
+
+impl EntityContainer for EntityBitSet {
+    fn new(entity: Entity) -> Self {
+        let mut set = Self::default();
+        set.push(entity);
+        set
+    }
+
+    fn empty() -> Self {
+        Self::default()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn clear(&mut self) {
+        self.bits.clear();
+        self.generations.clear();
+        self.len = 0;
+    }
+
+    fn contains(&self, entity: Entity) -> bool {
+        let (index, generation) = Self::split(entity);
+        self.is_set(index) && self.generations[index] == generation
+    }
+
+    fn push(&mut self, entity: Entity) {
+        let (index, generation) = Self::split(entity);
+        if index >= self.generations.len() {
+            self.generations.resize(index + 1, 0);
+            self.bits.resize(index / 64 + 1, 0);
+        }
+
+        // An entity with a stale generation is replaced, as it no longer exists.
+        if !self.is_set(index) {
+            self.bits[index / 64] |= 1 << (index % 64);
+            self.len += 1;
+        }
+        self.generations[index] = generation;
+    }
+
+    fn remove(&mut self, entity: Entity) {
+        if self.contains(entity) {
+            let index = entity.index() as usize;
+            self.bits[index / 64] &= !(1 << (index % 64));
+            self.len -= 1;
+        }
+    }
+
+    fn into_iter(self) -> impl Iterator<Item = Entity> {
+        IntoIterator::into_iter(EntityContainer::iter(&self).collect::<Vec<_>>())
+    }
+
+    fn iter(&self) -> impl Iterator<Item = Entity> {
+        self.bits.iter().enumerate().flat_map(move |(i, &word)| {
+            let mut word = word;
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+
+                let index = i * 64 + bit;
+                let high = u64::from(self.generations[index]);
+                Some(Entity::from_bits(high << 32 | index as u64))
+            })
+        })
+    }
+}
+
+impl PartialEq for EntityBitSet {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && EntityContainer::iter(self).eq(EntityContainer::iter(other))
+    }
+}
+
+impl Eq for EntityBitSet {}
+
+impl Debug for EntityBitSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(EntityContainer::iter(self)).finish()
+    }
+}
+
+impl FromIterator<Entity> for EntityBitSet {
+    fn from_iter<T: IntoIterator<Item = Entity>>(iter: T) -> Self {
+        let mut set = Self::default();
+        for entity in iter {
+            set.push(entity);
+        }
+        set
+    }
+}
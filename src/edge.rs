@@ -0,0 +1,75 @@
+use std::marker::PhantomData;
+
+use bevy_ecs::{
+    component::{Component, ComponentHooks, ComponentId, StorageType},
+    entity::Entity,
+    world::{DeferredWorld, World},
+};
+
+use crate::{commands::RelationCommands, related::Related, relation::Relation};
+
+/// [`Component`] on the edge entity of a link in a [`Relation::REIFIED`]
+/// relation, referring to both endpoints.
+///
+/// Despawning the edge entity, or removing this component from it, unrelates
+/// the endpoints.
+pub struct Edge<R: Relation> {
+    source: Entity,
+    target: Entity,
+    marker: PhantomData<fn(R)>,
+}
+
+impl<R: Relation> Edge<R> {
+    pub(crate) fn new(source: Entity, target: Entity) -> Self {
+        Self {
+            source,
+            target,
+            marker: PhantomData,
+        }
+    }
+
+    /// The endpoint on the [`Relation::Source`] side.
+    pub fn source(&self) -> Entity {
+        self.source
+    }
+
+    /// The endpoint on the [`Relation::Target`] side.
+    pub fn target(&self) -> Entity {
+        self.target
+    }
+}
+
+impl<R: Relation> Component for Edge<R> {
+    const STORAGE_TYPE: StorageType = StorageType::Table;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        hooks.on_remove(detach::<R>);
+    }
+}
+
+impl<R: Relation> std::fmt::Debug for Edge<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Edge")
+            .field("source", &self.source)
+            .field("target", &self.target)
+            .finish()
+    }
+}
+
+fn detach<R: Relation>(mut world: DeferredWorld, edge_id: Entity, _: ComponentId) {
+    let Some(edge) = world.get::<Edge<R>>(edge_id) else {
+        return;
+    };
+    let (source, target) = (edge.source, edge.target);
+
+    world.commands().queue(move |world: &mut World| {
+        // The link may already be gone, or have been given a new edge since.
+        let linked = world
+            .get::<Related<R::Source>>(source)
+            .is_some_and(|related| related.edge(target) == Some(edge_id));
+
+        if linked {
+            world.entity_mut(source).unrelate::<R::Source>(target);
+        }
+    });
+}
@@ -0,0 +1,86 @@
+use std::{error::Error, fmt};
+
+use bevy_ecs::{entity::Entity, event::Events, system::Resource, world::World};
+use bevy_utils::tracing::warn;
+
+use crate::{event::RelationErrorEvent, relation::Relatable};
+
+/// An error that occurred while maintaining a [`Relation`].
+///
+/// [`Relation`]: crate::relation::Relation
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RelationError {
+    /// The entity an operation was called on does not exist.
+    NoSuchEntity(Entity),
+    /// `entity` refers to `dangling`, which no longer exists.
+    Dangling { entity: Entity, dangling: Entity },
+    /// `entity` tried to relate to `target`, which refused because its
+    /// container is full and it uses [`ConflictPolicy::Reject`].
+    ///
+    /// [`ConflictPolicy::Reject`]: crate::relation::ConflictPolicy::Reject
+    Rejected { entity: Entity, target: Entity },
+    /// `entity` tried to relate to `target` without a slot, which the
+    /// container of `target` needs, e.g. [`NamedSlots`].
+    ///
+    /// [`NamedSlots`]: crate::container::NamedSlots
+    NoSlot { entity: Entity, target: Entity },
+    /// `entity` was expected to be related to `other`, but isn't.
+    NotRelated { entity: Entity, other: Entity },
+    /// The entity tried to relate to [`Entity::PLACEHOLDER`].
+    Placeholder(Entity),
+}
+
+impl fmt::Display for RelationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoSuchEntity(entity) => write!(f, "{entity} does not exist"),
+            Self::Dangling { entity, dangling } => {
+                write!(f, "{entity} refers to {dangling}, which no longer exists")
+            }
+            Self::Rejected { entity, target } => {
+                write!(f, "{target} refused to relate to {entity}, as it is full")
+            }
+            Self::NoSlot { entity, target } => {
+                write!(f, "{target} needs a slot to relate to {entity}")
+            }
+            Self::NotRelated { entity, other } => {
+                write!(f, "{entity} is not related to {other}")
+            }
+            Self::Placeholder(entity) => {
+                write!(f, "{entity} cannot relate to Entity::PLACEHOLDER")
+            }
+        }
+    }
+}
+
+impl Error for RelationError {}
+
+/// [`Resource`] that decides what happens when a [`RelationError`] occurs in
+/// deferred relation work, such as the component hooks.
+///
+/// Errors are sent as [`RelationErrorEvent`]s first, if those are initialized.
+/// Without this resource, errors are ignored.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum RelationErrorHandler {
+    /// Panic with the error.
+    Panic,
+    /// Log the error as a warning.
+    Warn,
+    /// Ignore the error.
+    #[default]
+    Ignore,
+}
+
+/// Sends the error as a [`RelationErrorEvent`], then passes it to the
+/// [`RelationErrorHandler`].
+pub(crate) fn report_error<N: Relatable>(world: &mut World, error: RelationError) {
+    if let Some(mut events) = world.get_resource_mut::<Events<RelationErrorEvent<N::Relation>>>() {
+        events.send(RelationErrorEvent::new(error));
+    }
+
+    match world.get_resource::<RelationErrorHandler>().copied() {
+        Some(RelationErrorHandler::Panic) => panic!("relation error: {error}"),
+        Some(RelationErrorHandler::Warn) => warn!("relation error: {error}"),
+        Some(RelationErrorHandler::Ignore) | None => {}
+    }
+}
@@ -1,36 +1,129 @@
 use core::fmt;
-use std::marker::PhantomData;
+use std::{marker::PhantomData, sync::Arc};
 
 use bevy_ecs::{entity::Entity, event::Event};
 
-use crate::relation::Relation;
+use crate::{
+    error::RelationError,
+    relation::{Relatable, Relation},
+};
 
 /// An [`Event`] that is emitted when a [`Relation`] is added or removed between
 /// two entities.
+///
+/// `source` is always the entity on the [`Relation::Source`] side and `target`
+/// the one on the [`Relation::Target`] side. For undirected relations, where
+/// both sides are the same, `source` is the entity whose change caused the
+/// event.
+///
+/// `data` is the [`Relation::Data`] payload of the edge, if it had one, which
+/// for removed edges is the payload it had before removal.
 #[derive(Event)]
 pub enum RelationEvent<R: Relation> {
-    Added(Entity, Entity, PhantomData<fn(R)>),
-    Removed(Entity, Entity, PhantomData<fn(R)>),
+    Added {
+        source: Entity,
+        target: Entity,
+        cause: RelationCause,
+        data: Option<Arc<R::Data>>,
+        marker: PhantomData<fn(R)>,
+    },
+    Removed {
+        source: Entity,
+        target: Entity,
+        cause: RelationCause,
+        data: Option<Arc<R::Data>>,
+        marker: PhantomData<fn(R)>,
+    },
 }
 
-impl<R: Relation> fmt::Debug for RelationEvent<R> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+impl<R: Relation> RelationEvent<R> {
+    /// Creates a [`RelationEvent::Added`] event.
+    pub fn added(source: Entity, target: Entity, cause: RelationCause) -> Self {
+        Self::Added {
+            source,
+            target,
+            cause,
+            data: None,
+            marker: PhantomData,
+        }
+    }
+
+    /// Creates a [`RelationEvent::Removed`] event.
+    pub fn removed(source: Entity, target: Entity, cause: RelationCause) -> Self {
+        Self::Removed {
+            source,
+            target,
+            cause,
+            data: None,
+            marker: PhantomData,
+        }
+    }
+
+    /// The entity on the source side of the relation.
+    pub fn source(&self) -> Entity {
+        match self {
+            Self::Added { source, .. } | Self::Removed { source, .. } => *source,
+        }
+    }
+
+    /// The entity on the target side of the relation.
+    pub fn target(&self) -> Entity {
+        match self {
+            Self::Added { target, .. } | Self::Removed { target, .. } => *target,
+        }
+    }
+
+    /// What caused the relation to be added or removed.
+    pub fn cause(&self) -> RelationCause {
         match self {
-            Self::Added(arg0, arg1, _) => f.debug_tuple("Added").field(arg0).field(arg1).finish(),
-            Self::Removed(arg0, arg1, _) => {
-                f.debug_tuple("Removed").field(arg0).field(arg1).finish()
-            }
+            Self::Added { cause, .. } | Self::Removed { cause, .. } => *cause,
         }
     }
+
+    /// The payload of the edge, if it had one.
+    pub fn data(&self) -> Option<&R::Data> {
+        match self {
+            Self::Added { data, .. } | Self::Removed { data, .. } => data.as_deref(),
+        }
+    }
+
+    /// Sets the payload of the edge.
+    pub fn with_data(mut self, payload: Option<Arc<R::Data>>) -> Self {
+        match &mut self {
+            Self::Added { data, .. } | Self::Removed { data, .. } => *data = payload,
+        }
+        self
+    }
+}
+
+impl<R: Relation> fmt::Debug for RelationEvent<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Added { .. } => "Added",
+            Self::Removed { .. } => "Removed",
+        };
+
+        f.debug_struct(name)
+            .field("source", &self.source())
+            .field("target", &self.target())
+            .field("cause", &self.cause())
+            .field("data", &self.data())
+            .finish()
+    }
 }
 
 impl<R: Relation> PartialEq for RelationEvent<R> {
     fn eq(&self, other: &Self) -> bool {
-        match (self, other) {
-            (Self::Added(l0, l1, _), Self::Added(r0, r1, _))
-            | (Self::Removed(l0, l1, _), Self::Removed(r0, r1, _)) => l0 == r0 && l1 == r1,
-            _ => false,
-        }
+        let same_kind = matches!(
+            (self, other),
+            (Self::Added { .. }, Self::Added { .. }) | (Self::Removed { .. }, Self::Removed { .. })
+        );
+
+        same_kind
+            && self.source() == other.source()
+            && self.target() == other.target()
+            && self.cause() == other.cause()
+            && self.data() == other.data()
     }
 }
 
@@ -38,9 +131,240 @@ impl<R: Relation> Eq for RelationEvent<R> {}
 
 impl<R: Relation> Clone for RelationEvent<R> {
     fn clone(&self) -> Self {
+        let event = match self {
+            Self::Added { .. } => Self::added(self.source(), self.target(), self.cause()),
+            Self::Removed { .. } => Self::removed(self.source(), self.target(), self.cause()),
+        };
+
         match self {
-            Self::Added(arg0, arg1, _) => Self::Added(*arg0, *arg1, PhantomData),
-            Self::Removed(arg0, arg1, _) => Self::Removed(*arg0, *arg1, PhantomData),
+            Self::Added { data, .. } | Self::Removed { data, .. } => event.with_data(data.clone()),
+        }
+    }
+}
+
+/// What caused a [`RelationEvent`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RelationCause {
+    /// A [`Related`] component was inserted.
+    ///
+    /// [`Related`]: crate::related::Related
+    Insert,
+    /// A [`Related`] component was replaced by another one.
+    ///
+    /// [`Related`]: crate::related::Related
+    Replace,
+    /// A [`Related`] component was removed.
+    ///
+    /// [`Related`]: crate::related::Related
+    Remove,
+    /// An entity was despawned.
+    Despawn,
+    /// A new link displaced this one under [`ConflictPolicy::Steal`].
+    ///
+    /// [`ConflictPolicy::Steal`]: crate::relation::ConflictPolicy::Steal
+    Steal,
+    /// A [`Related`] component was mutated in place, e.g. with
+    /// [`RelationCommands::modify_related`].
+    ///
+    /// [`Related`]: crate::related::Related
+    /// [`RelationCommands::modify_related`]: crate::commands::RelationCommands::modify_related
+    Mutation,
+    /// The relation was repaired with [`repair`].
+    ///
+    /// [`repair`]: crate::integrity::repair
+    Repair,
+}
+
+/// An observer [`Event`] triggered on both entities when a [`Relation`] is
+/// added between them.
+///
+/// `other` is the entity on the other end of the relation from the one the
+/// event is targeted at.
+#[derive(Event)]
+pub struct OnRelate<R: Relation> {
+    pub other: Entity,
+    marker: PhantomData<fn(R)>,
+}
+
+impl<R: Relation> OnRelate<R> {
+    pub fn new(other: Entity) -> Self {
+        Self {
+            other,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<R: Relation> fmt::Debug for OnRelate<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OnRelate")
+            .field("other", &self.other)
+            .finish()
+    }
+}
+
+impl<R: Relation> PartialEq for OnRelate<R> {
+    fn eq(&self, other: &Self) -> bool {
+        self.other == other.other
+    }
+}
+
+impl<R: Relation> Eq for OnRelate<R> {}
+
+impl<R: Relation> Clone for OnRelate<R> {
+    fn clone(&self) -> Self {
+        Self::new(self.other)
+    }
+}
+
+/// An observer [`Event`] triggered on both entities when a [`Relation`] is
+/// removed between them.
+///
+/// `other` is the entity on the other end of the relation from the one the
+/// event is targeted at.
+#[derive(Event)]
+pub struct OnUnrelate<R: Relation> {
+    pub other: Entity,
+    marker: PhantomData<fn(R)>,
+}
+
+impl<R: Relation> OnUnrelate<R> {
+    pub fn new(other: Entity) -> Self {
+        Self {
+            other,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<R: Relation> fmt::Debug for OnUnrelate<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OnUnrelate")
+            .field("other", &self.other)
+            .finish()
+    }
+}
+
+impl<R: Relation> PartialEq for OnUnrelate<R> {
+    fn eq(&self, other: &Self) -> bool {
+        self.other == other.other
+    }
+}
+
+impl<R: Relation> Eq for OnUnrelate<R> {}
+
+impl<R: Relation> Clone for OnUnrelate<R> {
+    fn clone(&self) -> Self {
+        Self::new(self.other)
+    }
+}
+
+/// An [`Event`] that is emitted when a [`RelationError`] occurs while
+/// maintaining a [`Relation`].
+#[derive(Event)]
+pub struct RelationErrorEvent<R: Relation> {
+    pub error: RelationError,
+    marker: PhantomData<fn(R)>,
+}
+
+impl<R: Relation> RelationErrorEvent<R> {
+    pub fn new(error: RelationError) -> Self {
+        Self {
+            error,
+            marker: PhantomData,
         }
     }
 }
+
+impl<R: Relation> fmt::Debug for RelationErrorEvent<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RelationErrorEvent")
+            .field(&self.error)
+            .finish()
+    }
+}
+
+impl<R: Relation> PartialEq for RelationErrorEvent<R> {
+    fn eq(&self, other: &Self) -> bool {
+        self.error == other.error
+    }
+}
+
+impl<R: Relation> Eq for RelationErrorEvent<R> {}
+
+impl<R: Relation> Clone for RelationErrorEvent<R> {
+    fn clone(&self) -> Self {
+        Self::new(self.error)
+    }
+}
+
+/// An [`Event`] that is emitted when the entities on the `N` side of an entity
+/// were rearranged without being added or removed, for containers where the
+/// order is meaningful.
+///
+/// Only rearrangements made through [`RelationCommands::modify_related`] are
+/// reported.
+///
+/// [`RelationCommands::modify_related`]: crate::commands::RelationCommands::modify_related
+#[derive(Event)]
+pub struct RelationReordered<N: Relatable> {
+    /// The entity whose related entities were reordered.
+    pub entity: Entity,
+    marker: PhantomData<fn(N)>,
+}
+
+impl<N: Relatable> RelationReordered<N> {
+    pub fn new(entity: Entity) -> Self {
+        Self {
+            entity,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<N: Relatable> fmt::Debug for RelationReordered<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RelationReordered")
+            .field("entity", &self.entity)
+            .finish()
+    }
+}
+
+impl<N: Relatable> PartialEq for RelationReordered<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.entity == other.entity
+    }
+}
+
+impl<N: Relatable> Eq for RelationReordered<N> {}
+
+impl<N: Relatable> Clone for RelationReordered<N> {
+    fn clone(&self) -> Self {
+        Self::new(self.entity)
+    }
+}
+
+/// An observer [`Event`] triggered on an entity that lost its last related
+/// entity on the `N` side, when `N` uses [`OrphanPolicy::Trigger`].
+///
+/// [`OrphanPolicy::Trigger`]: crate::relation::OrphanPolicy::Trigger
+#[derive(Event)]
+pub struct OnOrphan<N: Relatable>(PhantomData<fn(N)>);
+
+impl<N: Relatable> OnOrphan<N> {
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<N: Relatable> Default for OnOrphan<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N: Relatable> fmt::Debug for OnOrphan<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("OnOrphan").finish()
+    }
+}
@@ -0,0 +1,314 @@
+use std::{any::TypeId, fmt, marker::PhantomData};
+
+use bevy_ecs::{
+    entity::{Entity, EntityHashSet},
+    system::Resource,
+    world::{DeferredWorld, World},
+};
+
+use crate::{
+    container::EntityContainer,
+    event::RelationCause,
+    related::{dangling, despawn_edge, link, Related},
+    relation::{Relatable, Relation, Side},
+};
+
+/// A single link from one entity to another, as stored in the [`Related`]
+/// component on the given side of a relation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Link {
+    /// The side of the relation the `from` entity is on.
+    pub side: Side,
+    /// The entity holding the [`Related`] component.
+    pub from: Entity,
+    /// The entity stored in the [`Related`] component.
+    pub to: Entity,
+}
+
+/// The result of auditing a [`Relation`] with [`audit`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IntegrityReport {
+    /// Links that are not mirrored by the entity on the other side.
+    pub asymmetric: Vec<Link>,
+    /// Links that are stored more than once in the same container.
+    pub duplicates: Vec<Link>,
+    /// Links to entities that no longer exist.
+    pub dangling: Vec<Link>,
+    /// Links to [`Entity::PLACEHOLDER`].
+    pub placeholders: Vec<Link>,
+}
+
+impl IntegrityReport {
+    /// Returns `true` if no problems were found.
+    pub fn is_empty(&self) -> bool {
+        self.asymmetric.is_empty()
+            && self.duplicates.is_empty()
+            && self.dangling.is_empty()
+            && self.placeholders.is_empty()
+    }
+}
+
+impl fmt::Display for IntegrityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sections = [
+            ("asymmetric", &self.asymmetric),
+            ("duplicate", &self.duplicates),
+            ("dangling", &self.dangling),
+            ("placeholder", &self.placeholders),
+        ];
+
+        for (name, links) in sections {
+            for link in links {
+                writeln!(
+                    f,
+                    "{name} link on {:?} side: {} -> {}",
+                    link.side, link.from, link.to
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Walks every [`Related`] component on both sides of the relation `R` and
+/// reports any inconsistencies.
+pub fn audit<R: Relation>(world: &World) -> IntegrityReport {
+    let mut report = IntegrityReport::default();
+
+    audit_side::<R::Source>(world, Side::Source, &mut report);
+    if !is_undirected::<R>() {
+        audit_side::<R::Target>(world, Side::Target, &mut report);
+    }
+
+    report
+}
+
+/// Audits the relation `R` like [`audit`], then repairs every inconsistency
+/// that was found, returning the report from before the repair.
+///
+/// Placeholders, dangling references and duplicates are removed. Asymmetric
+/// links are mirrored if they are on the `authority` side, and removed
+/// otherwise. Undirected relations always mirror asymmetric links.
+pub fn repair<R: Relation>(world: &mut World, authority: Side) -> IntegrityReport {
+    let report = audit::<R>(world);
+
+    for link in report.placeholders.iter().chain(&report.dangling) {
+        match link.side {
+            Side::Source => remove_link::<R::Source>(world, link.from, link.to),
+            Side::Target => remove_link::<R::Target>(world, link.from, link.to),
+        }
+    }
+
+    for link in &report.duplicates {
+        match link.side {
+            Side::Source => dedupe_link::<R::Source>(world, link.from, link.to),
+            Side::Target => dedupe_link::<R::Target>(world, link.from, link.to),
+        }
+    }
+
+    for link in &report.asymmetric {
+        let mirror = link.side == authority || is_undirected::<R>();
+        match (link.side, mirror) {
+            (Side::Source, true) => mirror_link::<R::Source>(world, link.from, link.to),
+            (Side::Target, true) => mirror_link::<R::Target>(world, link.from, link.to),
+            (Side::Source, false) => remove_link::<R::Source>(world, link.from, link.to),
+            (Side::Target, false) => remove_link::<R::Target>(world, link.from, link.to),
+        }
+    }
+
+    report
+}
+
+/// [`Resource`] that makes the relation `R` audit itself during each flush,
+/// once the work queued by its hooks has been applied, and panic if any
+/// inconsistencies are found.
+///
+/// It only has an effect when debug assertions are enabled, and is meant to
+/// catch hook bugs in test suites. Every audit walks the whole world, so it
+/// slows down large worlds considerably. Links kept to despawned entities
+/// under [`DespawnPolicy::Keep`] are reported as dangling.
+///
+/// [`DespawnPolicy::Keep`]: crate::relation::DespawnPolicy::Keep
+#[derive(Resource)]
+pub struct AssertIntegrity<R: Relation> {
+    pending: usize,
+    marker: PhantomData<fn(R)>,
+}
+
+impl<R: Relation> Default for AssertIntegrity<R> {
+    fn default() -> Self {
+        Self {
+            pending: 0,
+            marker: PhantomData,
+        }
+    }
+}
+
+/// Queues [`assert_integrity`] after the work just queued by a hook of `R`, if
+/// [`AssertIntegrity<R>`] is enabled.
+///
+/// Hooks fire while other hooks' work is applied, so the audit only runs once
+/// no work is pending anymore, as the state in between is transient.
+pub(crate) fn queue_assert_integrity<R: Relation>(world: &mut DeferredWorld) {
+    if !cfg!(debug_assertions) {
+        return;
+    }
+
+    let Some(mut checks) = world.get_resource_mut::<AssertIntegrity<R>>() else {
+        return;
+    };
+    checks.pending += 1;
+
+    world.commands().queue(|world: &mut World| {
+        let Some(mut checks) = world.get_resource_mut::<AssertIntegrity<R>>() else {
+            return;
+        };
+        checks.pending = checks.pending.saturating_sub(1);
+
+        if checks.pending == 0 {
+            assert_integrity::<R>(world);
+        }
+    });
+}
+
+/// A system that audits the relation `R` and panics if any inconsistencies
+/// are found, when debug assertions are enabled.
+///
+/// Add it to a schedule after commands have been applied, so that transient
+/// states in between the relation hooks are not reported. To audit after
+/// every hook instead, insert the [`AssertIntegrity<R>`] resource.
+pub fn assert_integrity<R: Relation>(world: &World) {
+    if cfg!(debug_assertions) {
+        let report = audit::<R>(world);
+        assert!(
+            report.is_empty(),
+            "relation `{}` is inconsistent:\n{report}",
+            std::any::type_name::<R>()
+        );
+    }
+}
+
+/// Removes every reference to an entity that no longer exists from the
+/// [`Related`] components on both sides of the relation `R`.
+///
+/// Each pruned reference is reported according to the [`DanglingPolicy`] of
+/// its side. Returns the number of references that were pruned.
+///
+/// [`DanglingPolicy`]: crate::relation::DanglingPolicy
+pub fn prune_dangling<R: Relation>(world: &mut World) -> usize {
+    let mut pruned = prune_side::<R::Source>(world);
+
+    // Undirected relations have a single side, which has already been pruned.
+    if !is_undirected::<R>() {
+        pruned += prune_side::<R::Target>(world);
+    }
+
+    pruned
+}
+
+fn is_undirected<R: Relation>() -> bool {
+    TypeId::of::<R::Source>() == TypeId::of::<R::Target>()
+}
+
+fn audit_side<N: Relatable>(world: &World, side: Side, report: &mut IntegrityReport) {
+    let related = world
+        .iter_entities()
+        .filter_map(|entity| Some((entity.id(), entity.get::<Related<N>>()?)));
+
+    for (from, related) in related {
+        let mut seen = EntityHashSet::default();
+        let mut duplicates = EntityHashSet::default();
+
+        for to in related.iter() {
+            let link = Link { side, from, to };
+
+            if !seen.insert(to) {
+                if duplicates.insert(to) {
+                    report.duplicates.push(link);
+                }
+                continue;
+            }
+
+            if to == Entity::PLACEHOLDER {
+                report.placeholders.push(link);
+            } else if world.get_entity(to).is_err() {
+                report.dangling.push(link);
+            } else if !world
+                .get::<Related<N::Opposite>>(to)
+                .is_some_and(|opposite| opposite.contains(from))
+            {
+                report.asymmetric.push(link);
+            }
+        }
+    }
+}
+
+fn prune_side<N: Relatable>(world: &mut World) -> usize {
+    let mut query = world.query::<(Entity, &Related<N>)>();
+    let dangling_refs = query
+        .iter(world)
+        .map(|(a_id, a_related)| {
+            let b_ids = a_related
+                .iter()
+                .filter(|&b_id| world.get_entity(b_id).is_err())
+                .collect::<Vec<_>>();
+            (a_id, b_ids)
+        })
+        .filter(|(_, b_ids)| !b_ids.is_empty())
+        .collect::<Vec<_>>();
+
+    let mut pruned = 0;
+    for (a_id, b_ids) in &dangling_refs {
+        dangling::<N>(world, *a_id, b_ids);
+        pruned += b_ids.len();
+    }
+
+    pruned
+}
+
+/// Removes `to` from the [`Related`] component of `from` in place, without
+/// touching the other side, and despawns its edge entity, if any.
+fn remove_link<N: Relatable>(world: &mut World, from: Entity, to: Entity) {
+    let Ok(mut entity) = world.get_entity_mut(from) else {
+        return;
+    };
+
+    let Some(mut related) = entity.get_mut::<Related<N>>() else {
+        return;
+    };
+
+    let edge = related.edge(to);
+    related.remove(to);
+
+    if related.is_empty() {
+        entity.remove::<Related<N>>();
+    }
+
+    if let Some(edge) = edge {
+        despawn_edge(world, edge);
+    }
+}
+
+/// Collapses every copy of `to` in the [`Related`] component of `from` into
+/// the first one, in place, so that its position, multiplicity, payload and
+/// edge entity are kept.
+fn dedupe_link<N: Relatable>(world: &mut World, from: Entity, to: Entity) {
+    if let Some(mut related) = world.get_mut::<Related<N>>(from) {
+        let mut seen = false;
+        related
+            .container
+            .retain(|id| id != to || !std::mem::replace(&mut seen, true));
+    }
+}
+
+/// Adds `from` to the [`Related`] component of `to`, if it is still missing.
+fn mirror_link<N: Relatable>(world: &mut World, from: Entity, to: Entity) {
+    let mirrored = world
+        .get::<Related<N::Opposite>>(to)
+        .is_some_and(|opposite| opposite.contains(from));
+
+    if !mirrored {
+        link::<N>(world, from, to, RelationCause::Repair);
+    }
+}
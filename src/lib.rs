@@ -1,17 +1,28 @@
+pub mod changes;
+pub mod coalesce;
+pub mod commands;
 pub mod container;
+pub mod edge;
+pub mod error;
 pub mod event;
+pub mod integrity;
 pub mod query;
 pub mod related;
 pub mod relation;
+pub mod traversal;
 
 pub mod prelude {
     //! Re-exports the most commonly used traits and types.
 
     pub use crate::{
+        changes::RelationChanges,
+        commands::{RelationCommands, TryRelationCommands},
         container::EntityContainer,
-        event::RelationEvent,
-        query::{BothRelated, EitherRelated, SelectRelated, SelectRelatedItem},
+        edge::Edge,
+        event::{RelationCause, RelationEvent},
+        query::{BothRelated, EitherRelated, RelatedQuery, SelectRelated, SelectRelatedItem},
         related::Related,
         relation::{Relatable, Relation},
+        traversal::{Hierarchy, Traverse},
     };
 }
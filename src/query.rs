@@ -1,6 +1,13 @@
-use bevy_ecs::query::{QueryData, ReadOnlyQueryData};
+use bevy_ecs::{
+    entity::Entity,
+    query::{QueryData, QueryFilter, ROQueryItem, ReadOnlyQueryData},
+    system::{Query, SystemParam},
+};
 
-use crate::{prelude::Relation, related::Related};
+use crate::{
+    prelude::{Relatable, Relation},
+    related::Related,
+};
 
 /// [`QueryData`] wrapper for fetching the sides of a [`Relation`].
 ///
@@ -23,6 +30,76 @@ pub type BothRelated<R> = SelectRelated<R, Required, Required>;
 /// [`Option`]s, allowing either or both to be absent.
 pub type EitherRelated<R> = SelectRelated<R, Optional, Optional>;
 
+/// [`SystemParam`] joining the [`Related<N>`] component of an entity with
+/// the `D` query data of the related entities, filtered by `F`.
+///
+/// Read-only access yields all related items at once, while mutable access
+/// hands out one item at a time, so two items can never alias. Like any
+/// [`SystemParam`], `D` must not access [`Related<N>`] mutably.
+#[derive(SystemParam)]
+pub struct RelatedQuery<'w, 's, N, D, F = ()>
+where
+    N: Relatable,
+    D: QueryData + 'static,
+    F: QueryFilter + 'static,
+{
+    related: Query<'w, 's, &'static Related<N>>,
+    data: Query<'w, 's, D, F>,
+}
+
+impl<N, D, F> RelatedQuery<'_, '_, N, D, F>
+where
+    N: Relatable,
+    D: QueryData + 'static,
+    F: QueryFilter + 'static,
+{
+    /// Returns the entities related to the given entity that match the query,
+    /// along with their read-only query items.
+    pub fn iter(&self, entity: Entity) -> impl Iterator<Item = (Entity, ROQueryItem<'_, D>)> {
+        self.related
+            .get(entity)
+            .into_iter()
+            .flat_map(|related| related.iter())
+            .filter_map(|id| Some((id, self.data.get(id).ok()?)))
+    }
+
+    /// Returns the read-only query item of `other`, if it is related to the
+    /// given entity and matches the query.
+    pub fn get(&self, entity: Entity, other: Entity) -> Option<ROQueryItem<'_, D>> {
+        self.contains(entity, other)
+            .then(|| self.data.get(other).ok())
+            .flatten()
+    }
+
+    /// Returns the query item of `other`, if it is related to the given
+    /// entity and matches the query.
+    pub fn get_mut(&mut self, entity: Entity, other: Entity) -> Option<D::Item<'_>> {
+        self.contains(entity, other)
+            .then(|| self.data.get_mut(other).ok())
+            .flatten()
+    }
+
+    /// Calls `f` with each entity related to the given entity that matches
+    /// the query, along with its query item.
+    pub fn for_each_mut(&mut self, entity: Entity, mut f: impl FnMut(Entity, D::Item<'_>)) {
+        let Ok(related) = self.related.get(entity) else {
+            return;
+        };
+
+        for id in related.iter() {
+            if let Ok(item) = self.data.get_mut(id) {
+                f(id, item);
+            }
+        }
+    }
+
+    fn contains(&self, entity: Entity, other: Entity) -> bool {
+        self.related
+            .get(entity)
+            .is_ok_and(|related| related.contains(other))
+    }
+}
+
 /// A trait providing a type function that determines the type of the item
 /// fetched by a [`SelectRelated`] query.
 pub trait Selector {
@@ -1,37 +1,90 @@
-use std::{any::type_name, marker::PhantomData};
+use std::{
+    any::{type_name, TypeId},
+    collections::{BTreeSet, VecDeque},
+    ops::RangeBounds,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
 
 use bevy_ecs::{
     component::{Component, ComponentHooks, ComponentId, StorageType},
-    entity::Entity,
-    event::Events,
+    entity::{Entity, EntityHashMap, EntityHashSet},
+    event::{Event, Events},
     world::{DeferredWorld, World},
 };
 
-use crate::{container::EntityContainer, event::RelationEvent, relation::Relatable};
+use bevy_utils::tracing::warn;
+
+use crate::{
+    changes::RelationChangeLog,
+    coalesce::CoalescedRelationEvents,
+    container::{
+        EntityContainer, MultiEntities, NamedSlots, OrderedEntities, SingleEntityContainer, SlotKey,
+    },
+    edge::Edge,
+    error::{report_error, RelationError},
+    event::{OnOrphan, OnRelate, OnUnrelate, RelationCause, RelationEvent, RelationReordered},
+    integrity::queue_assert_integrity,
+    relation::{ConflictPolicy, DanglingPolicy, DespawnPolicy, OrphanPolicy, Relatable, Relation},
+};
 
 /// [`Component`] used to store [`Relation`] data for a given side of a relationship,
 /// i.e. the [`Relatable`].
 ///
+/// Each related entity may carry a payload of the relation's
+/// [`Relation::Data`] type, shared with the opposite side. In a
+/// [`Relation::REIFIED`] relation, each related entity also has an edge entity.
+///
 /// [`Relation`]: crate::relation::Relation
 pub struct Related<N: Relatable> {
     pub(crate) container: N::Container,
+    pub(crate) data: EntityHashMap<Arc<RelationData<N>>>,
+    pub(crate) edges: EntityHashMap<Entity>,
+    /// Set by the `on_remove` hook, so that the work queued by the `on_replace`
+    /// hook right before it knows the component was removed, not replaced.
+    pub(crate) removed: Arc<AtomicBool>,
 }
 
+/// The edge payload type of the relation that `N` is a side of.
+pub type RelationData<N> = <<N as Relatable>::Relation as Relation>::Data;
+
 impl<N: Relatable> Component for Related<N> {
     const STORAGE_TYPE: StorageType = StorageType::Table;
 
     fn register_component_hooks(hooks: &mut ComponentHooks) {
         hooks.on_insert(associate::<N>);
         hooks.on_replace(disassociate::<N>);
-        hooks.on_remove(disassociate::<N>);
+        hooks.on_remove(mark_removed::<N>);
     }
 }
 
 impl<N: Relatable> Related<N> {
+    /// Creates a component relating to the entities in the given container.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the container holds [`Entity::PLACEHOLDER`].
     pub fn new(node: impl Into<N::Container>) -> Self {
-        Self {
-            container: node.into(),
-        }
+        let container = node.into();
+        assert_not_placeholder::<N>(container.contains(Entity::PLACEHOLDER));
+
+        let mut related = Self {
+            container,
+            data: EntityHashMap::default(),
+            edges: EntityHashMap::default(),
+            removed: Arc::default(),
+        };
+        related.imply_all_data();
+        related
+    }
+
+    /// Creates a component relating to `entity`, with the given edge payload.
+    pub fn with_data(entity: Entity, data: RelationData<N>) -> Self {
+        let mut related = Self::from(entity);
+        related.data.insert(entity, Arc::new(data));
+        related
     }
 
     pub fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
@@ -41,19 +94,320 @@ impl<N: Relatable> Related<N> {
     pub fn contains(&self, entity: Entity) -> bool {
         self.container.contains(entity)
     }
+
+    /// Returns the payload of the edge to `entity`, if it has one.
+    pub fn data(&self, entity: Entity) -> Option<&RelationData<N>> {
+        self.data.get(&entity).map(Arc::as_ref)
+    }
+
+    /// Returns the edge entity of the link to `entity`, if the relation is
+    /// [`Relation::REIFIED`].
+    pub fn edge(&self, entity: Entity) -> Option<Entity> {
+        self.edges.get(&entity).copied()
+    }
+
+    /// Returns an iterator over the related entities and their edge entities.
+    pub fn iter_with_edges(&self) -> impl Iterator<Item = (Entity, Option<Entity>)> + '_ {
+        self.iter().map(|entity| (entity, self.edge(entity)))
+    }
+
+    /// Returns an iterator over the related entities and their edge payloads.
+    pub fn iter_with_data(&self) -> impl Iterator<Item = (Entity, Option<&RelationData<N>>)> + '_ {
+        self.iter().map(|entity| (entity, self.data(entity)))
+    }
+
+    /// Returns the number of entities this entity is related to.
+    pub fn len(&self) -> usize {
+        self.container.len()
+    }
+
+    /// Returns `true` if this entity is not related to any other entities.
+    pub fn is_empty(&self) -> bool {
+        self.container.is_empty()
+    }
+
+    /// Adds the given entity, unless it is already present.
+    ///
+    /// This only changes the component; use [`RelationCommands::modify_related`]
+    /// to keep the opposite side in sync.
+    ///
+    /// [`Entity::PLACEHOLDER`] is ignored, and panics with debug assertions.
+    /// Containers that need a slot, such as [`NamedSlots`], ignore the entity
+    /// as well; use [`Related::set_slot`] instead.
+    ///
+    /// [`RelationCommands::modify_related`]: crate::commands::RelationCommands::modify_related
+    pub fn push(&mut self, entity: Entity) {
+        debug_assert_ne!(
+            entity,
+            Entity::PLACEHOLDER,
+            "{} cannot relate to Entity::PLACEHOLDER",
+            type_name::<N>()
+        );
+        if entity == Entity::PLACEHOLDER {
+            return;
+        }
+
+        if !self.container.contains(entity) {
+            let displaced = self.container.displaced_by(entity);
+            self.container.push(entity);
+
+            // The container may have displaced an entity to make room.
+            if let Some(displaced) = displaced {
+                self.data.remove(&displaced);
+                self.edges.remove(&displaced);
+            }
+        }
+    }
+
+    /// Adds the given entity with the given edge payload, replacing the payload
+    /// if the entity is already present.
+    pub fn push_with_data(&mut self, entity: Entity, data: RelationData<N>) {
+        self.push(entity);
+        self.set_data(entity, data);
+    }
+
+    /// Sets the payload of the edge to `entity`, returning `false` if this
+    /// entity isn't related to it.
+    pub fn set_data(&mut self, entity: Entity, data: RelationData<N>) -> bool {
+        let related = self.contains(entity);
+        if related {
+            self.data.insert(entity, Arc::new(data));
+        }
+        related
+    }
+
+    /// Removes the given entity, if present.
+    pub fn remove(&mut self, entity: Entity) {
+        self.container.remove(entity);
+        self.data.remove(&entity);
+        self.edges.remove(&entity);
+    }
+
+    /// Keeps only the entities for which `f` returns `true`.
+    pub fn retain(&mut self, mut f: impl FnMut(Entity) -> bool) {
+        let mut removed = EntityHashSet::default();
+        self.container.retain(|id| {
+            let keep = f(id);
+            if !keep {
+                removed.insert(id);
+            }
+            keep
+        });
+
+        if !removed.is_empty() {
+            self.data.retain(|id, _| !removed.contains(id));
+            self.edges.retain(|id, _| !removed.contains(id));
+        }
+    }
+
+    /// Removes all entities.
+    pub fn clear(&mut self) {
+        self.container.clear();
+        self.data.clear();
+        self.edges.clear();
+    }
+
+    /// Gives `entity` the payload implied by the container, unless it already
+    /// has one.
+    fn imply_data(&mut self, entity: Entity) {
+        if self.data.contains_key(&entity) {
+            return;
+        }
+
+        let implied = self.container.implied_data(entity);
+        if let Some(data) = implied.and_then(|data| data.downcast::<RelationData<N>>().ok()) {
+            self.data.insert(entity, Arc::from(data));
+        }
+    }
+
+    /// Gives every entity without a payload the one implied by the container.
+    pub(crate) fn imply_all_data(&mut self) {
+        let Self {
+            container, data, ..
+        } = self;
+
+        for entity in container.iter() {
+            let implied = container.implied_data(entity);
+            if let Some(implied) = implied.and_then(|data| data.downcast::<RelationData<N>>().ok())
+            {
+                data.entry(entity).or_insert_with(|| Arc::from(implied));
+            }
+        }
+    }
+}
+
+impl<N: Relatable> Related<N>
+where
+    N::Container: SingleEntityContainer,
+{
+    /// Returns the entity this entity is related to, if any.
+    pub fn get(&self) -> Option<Entity> {
+        self.container.get()
+    }
+}
+
+impl<N: Relatable<Container = OrderedEntities>> Related<N> {
+    /// Returns the index of the given entity, if present.
+    pub fn index_of(&self, entity: Entity) -> Option<usize> {
+        self.container.index_of(entity)
+    }
+
+    /// Returns the entity at the given index, if any.
+    pub fn at(&self, index: usize) -> Option<Entity> {
+        self.container.at(index)
+    }
+
+    /// Inserts the given entity at `index`, or moves it there if it is
+    /// already present.
+    ///
+    /// Like [`Related::push`], this only changes the component.
+    pub fn insert_at(&mut self, index: usize, entity: Entity) {
+        self.container.insert_at(index, entity);
+    }
+
+    /// Moves the given entity to `index`, returning `false` if it isn't present.
+    pub fn move_to(&mut self, entity: Entity, index: usize) -> bool {
+        self.container.move_to(entity, index)
+    }
+
+    /// Swaps the positions of the two given entities, returning `false` if
+    /// either isn't present.
+    pub fn swap(&mut self, a: Entity, b: Entity) -> bool {
+        self.container.swap(a, b)
+    }
+
+    /// Sorts the entities by the key returned by `f`.
+    pub fn sort_by_key<K: Ord>(&mut self, mut f: impl FnMut(Entity) -> K) {
+        self.container.sort_by_key(|&entity| f(entity));
+    }
+}
+
+impl<N: Relatable<Container = BTreeSet<Entity>>> Related<N> {
+    /// Returns the related entities within the given range, in ascending order.
+    pub fn range(&self, range: impl RangeBounds<Entity>) -> impl Iterator<Item = Entity> + '_ {
+        self.container.range(range).copied()
+    }
+
+    /// Returns the lowest related entity, if any.
+    pub fn first(&self) -> Option<Entity> {
+        self.container.first().copied()
+    }
+
+    /// Returns the highest related entity, if any.
+    pub fn last(&self) -> Option<Entity> {
+        self.container.last().copied()
+    }
+}
+
+impl<N: Relatable<Container = NamedSlots<K>>, K: SlotKey> Related<N> {
+    /// Creates a component relating to `entity` in the given slot.
+    pub fn with_slot(slot: K, entity: Entity) -> Self {
+        let mut related = Self::new(NamedSlots::default());
+        related.set_slot(slot, entity);
+        related
+    }
+
+    /// Returns the entity in the given slot, if any.
+    pub fn get_slot(&self, slot: K) -> Option<Entity> {
+        self.container.get(slot)
+    }
+
+    /// Returns the slot the given entity occupies, if any.
+    pub fn slot_of(&self, entity: Entity) -> Option<K> {
+        self.container.slot_of(entity)
+    }
+
+    /// Returns an iterator over the occupied slots and their entities.
+    pub fn iter_slots(&self) -> impl Iterator<Item = (K, Entity)> + '_ {
+        self.container.iter_slots()
+    }
+
+    /// Puts the given entity in the given slot, moving it out of any other
+    /// slot and replacing the previous occupant, if any.
+    ///
+    /// When the relation's payload is the slot key, the entity is given its
+    /// new slot as payload, so that the entity on the other side can look it
+    /// up with [`Related::data`]. Like [`Related::push`], this only changes the
+    /// component.
+    pub fn set_slot(&mut self, slot: K, entity: Entity) {
+        debug_assert_ne!(
+            entity,
+            Entity::PLACEHOLDER,
+            "{} cannot relate to Entity::PLACEHOLDER",
+            type_name::<N>()
+        );
+        if entity == Entity::PLACEHOLDER {
+            return;
+        }
+
+        if let Some(displaced) = self.container.set(slot, entity) {
+            self.data.remove(&displaced);
+            self.edges.remove(&displaced);
+        }
+
+        let implied = self.container.implied_data(entity);
+        if let Some(data) = implied.and_then(|data| data.downcast::<RelationData<N>>().ok()) {
+            self.data.insert(entity, Arc::from(data));
+        }
+    }
+
+    /// Empties the given slot, returning the entity it held, if any.
+    pub fn clear_slot(&mut self, slot: K) -> Option<Entity> {
+        let entity = self.container.get(slot)?;
+        self.remove(entity);
+        Some(entity)
+    }
+}
+
+impl<N: Relatable<Container = MultiEntities>> Related<N> {
+    /// Returns the number of edges to the given entity.
+    pub fn multiplicity(&self, entity: Entity) -> usize {
+        self.container.multiplicity(entity)
+    }
+
+    /// Adds an edge to the given entity, returning its new multiplicity.
+    ///
+    /// Like [`Related::push`], this only changes the component.
+    pub fn add_edge(&mut self, entity: Entity) -> usize {
+        self.container.add_edge(entity)
+    }
+
+    /// Removes a single edge to the given entity, returning its remaining
+    /// multiplicity. The entity is removed once it has no edges left.
+    pub fn remove_one_edge(&mut self, entity: Entity) -> usize {
+        let count = self.container.remove_one_edge(entity);
+        if count == 0 {
+            self.remove(entity);
+        }
+        count
+    }
+}
+
+impl<N: Relatable> Extend<Entity> for Related<N> {
+    fn extend<T: IntoIterator<Item = Entity>>(&mut self, iter: T) {
+        for entity in iter {
+            self.push(entity);
+        }
+    }
 }
 
 impl<N: Relatable> Clone for Related<N> {
     fn clone(&self) -> Self {
         Self {
             container: self.container.clone(),
+            data: self.data.clone(),
+            edges: self.edges.clone(),
+            removed: Arc::default(),
         }
     }
 }
 
+/// Two components are equal if their containers are equal and they hold equal
+/// payloads. Edge entities are not compared, as they are spawned and restored
+/// by the relation itself.
 impl<N: Relatable> PartialEq for Related<N> {
     fn eq(&self, other: &Self) -> bool {
-        self.container == other.container
+        self.container == other.container && self.data == other.data
     }
 }
 
@@ -64,15 +418,29 @@ impl<N: Relatable> std::fmt::Debug for Related<N> {
         f.debug_tuple("Related")
             .field(&type_name::<N>())
             .field(&self.container)
+            .field(&self.data)
+            .field(&self.edges)
             .finish()
     }
 }
 
+/// Relates to the given entity.
+///
+/// # Panics
+///
+/// Panics if the entity is [`Entity::PLACEHOLDER`].
 impl<N: Relatable> From<Entity> for Related<N> {
     fn from(entity: Entity) -> Self {
-        Self {
+        assert_not_placeholder::<N>(entity == Entity::PLACEHOLDER);
+
+        let mut related = Self {
             container: N::Container::new(entity),
-        }
+            data: EntityHashMap::default(),
+            edges: EntityHashMap::default(),
+            removed: Arc::default(),
+        };
+        related.imply_data(entity);
+        related
     }
 }
 
@@ -81,12 +449,30 @@ where
     N::Container: FromIterator<Entity>,
 {
     fn from_iter<T: IntoIterator<Item = Entity>>(iter: T) -> Self {
-        Self {
-            container: N::Container::from_iter(iter),
-        }
+        let mut seen = EntityHashSet::default();
+        let container = N::Container::from_iter(iter.into_iter().filter(|&id| seen.insert(id)));
+        assert_not_placeholder::<N>(container.contains(Entity::PLACEHOLDER));
+
+        let mut related = Self {
+            container,
+            data: EntityHashMap::default(),
+            edges: EntityHashMap::default(),
+            removed: Arc::default(),
+        };
+        related.imply_all_data();
+        related
     }
 }
 
+#[track_caller]
+fn assert_not_placeholder<N: Relatable>(placeholder: bool) {
+    assert!(
+        !placeholder,
+        "{} cannot relate to Entity::PLACEHOLDER",
+        type_name::<N>()
+    );
+}
+
 fn associate<N: Relatable>(mut world: DeferredWorld, a_id: Entity, _: ComponentId) {
     world.commands().queue(move |world: &mut World| {
         // Get the IDs of the other entities that this entity is related to.
@@ -94,77 +480,602 @@ fn associate<N: Relatable>(mut world: DeferredWorld, a_id: Entity, _: ComponentI
             return;
         };
 
+        // Prune the entities that no longer exist before linking the rest.
+        let (live, despawned): (Vec<_>, Vec<_>) = a_related
+            .iter()
+            .partition(|&b_id| world.get_entity(b_id).is_ok());
+        dangling::<N>(world, a_id, &despawned);
+
         // For each other related entity, associate them with this entity.
-        for b_id in a_related.iter() {
-            let Ok(mut b) = world.get_entity_mut(b_id) else {
-                return;
+        for b_id in live {
+            let Ok(b) = world.get_entity(b_id) else {
+                continue;
             };
 
-            let b_related = b.get::<Related<N::Opposite>>();
-
-            let b_points_to_a = b_related.is_some_and(|b| b.contains(a_id));
-            if !b_points_to_a {
-                if let Some(b_related) = b_related {
-                    // The other entity is already related to some entities, so add this entity to the list.
-                    let mut b_related = b_related.clone();
-                    b_related.container.push(a_id);
-                    b.insert(b_related);
-                } else {
-                    // The other entity is not yet related to any entities, so relate it to this entity.
-                    let b_related = Related::<N::Opposite>::from(a_id);
-                    b.insert(b_related);
-                }
-
-                if let Some(mut events) =
-                    world.get_resource_mut::<Events<RelationEvent<N::Relation>>>()
-                {
-                    events.send(RelationEvent::Added(a_id, b_id, PhantomData));
-                }
+            let b_points_to_a = b
+                .get::<Related<N::Opposite>>()
+                .is_some_and(|b| b.contains(a_id));
+            if b_points_to_a {
+                share_data::<N>(world, a_id, b_id);
+                share_multiplicity::<N>(world, a_id, b_id);
+                reify::<N>(world, a_id, b_id);
+            } else {
+                link::<N>(world, a_id, b_id, RelationCause::Insert);
             }
         }
     });
+
+    queue_assert_integrity::<N::Relation>(&mut world);
 }
 
 fn disassociate<N: Relatable>(mut world: DeferredWorld, a_id: Entity, _: ComponentId) {
     // Gets the IDs of the entities that this entity is no longer related to.
-    let Some(b_ids) = world.get::<Related<N>>(a_id).cloned() else {
+    let Some(a_related) = world.get::<Related<N>>(a_id) else {
         return;
     };
+    let b_ids = a_related.clone();
+    let removed = a_related.removed.clone();
 
     world.commands().queue(move |world: &mut World| {
+        // The hooks recorded whether the component was replaced or removed,
+        // and a removed component whose entity is gone was despawned.
+        let cause = match (removed.load(Ordering::Relaxed), world.get_entity(a_id)) {
+            (false, _) => RelationCause::Replace,
+            (true, Ok(_)) => RelationCause::Remove,
+            (true, Err(_)) => RelationCause::Despawn,
+        };
+
+        // If this entity was despawned, the related entities are handled according to the policy.
+        if cause == RelationCause::Despawn {
+            match N::ON_DESPAWN {
+                DespawnPolicy::Cascade => return cascade::<N>(world, a_id, &b_ids),
+                DespawnPolicy::Detach => {}
+                DespawnPolicy::Keep => return,
+            }
+        }
+
         // For each related entity, disassociate it from this entity.
         for b_id in b_ids.iter() {
             let a_points_to_b = world
                 .get::<Related<N>>(a_id)
                 .is_some_and(|a_related| a_related.contains(b_id));
 
-            let Ok(mut b) = world.get_entity_mut(b_id) else {
-                return;
+            // The other entity was despawned as well, so there is nothing to disassociate.
+            let Ok(b) = world.get_entity(b_id) else {
+                continue;
             };
 
-            let b_related = b.get::<Related<N::Opposite>>();
-
-            let b_points_to_a = b_related.is_some_and(|b| b.contains(a_id));
+            let b_points_to_a = b
+                .get::<Related<N::Opposite>>()
+                .is_some_and(|b| b.contains(a_id));
             if b_points_to_a && !a_points_to_b {
-                if let Some(b_related) = b_related {
-                    // The other entity is related to some entities, so make sure this entity is removed from the list.
-                    let mut b_related = b_related.clone();
-                    b_related.container.remove(a_id);
-
-                    // If the other entity is no longer related to any entities, remove the component.
-                    if b_related.container.is_empty() {
-                        b.remove::<Related<N::Opposite>>();
-                    } else {
-                        b.insert(b_related);
-                    }
-                }
-
-                if let Some(mut events) =
-                    world.get_resource_mut::<Events<RelationEvent<N::Relation>>>()
-                {
-                    events.send(RelationEvent::Removed(a_id, b_id, PhantomData));
-                }
+                unlink::<N>(world, a_id, b_id, cause);
+            }
+        }
+    });
+
+    queue_assert_integrity::<N::Relation>(&mut world);
+}
+
+/// Marks the [`Related<N>`] component of `a_id` as removed for the work that
+/// [`disassociate`] queued when the `on_replace` hook fired right before.
+fn mark_removed<N: Relatable>(world: DeferredWorld, a_id: Entity, _: ComponentId) {
+    if let Some(a_related) = world.get::<Related<N>>(a_id) {
+        a_related.removed.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Despawns the entities `a_id` was related to before it was despawned and,
+/// breadth-first, every entity reachable from them through [`Related<N>`].
+///
+/// Every link that is broken along the way is unlinked with
+/// [`RelationCause::Despawn`] before its entities are despawned, skipping the
+/// [`OrphanPolicy`] of entities that are despawned anyway. Each entity is
+/// visited at most once, so cycles are fine, and the [`Related<N>`] component is
+/// emptied before despawning so that the hooks don't recurse.
+fn cascade<N: Relatable>(world: &mut World, a_id: Entity, b_ids: &Related<N>) {
+    let mut visited = EntityHashSet::default();
+    visited.insert(a_id);
+
+    for b_id in b_ids.iter() {
+        detach::<N>(world, a_id, b_id, RelationCause::Despawn);
+    }
+
+    let mut queue = b_ids.iter().collect::<VecDeque<_>>();
+    while let Some(id) = queue.pop_front() {
+        if !visited.insert(id) {
+            continue;
+        }
+
+        let Some(children) = world.get::<Related<N>>(id).cloned() else {
+            if let Ok(entity) = world.get_entity_mut(id) {
+                entity.despawn();
+            }
+            continue;
+        };
+
+        for child in children.iter() {
+            detach::<N>(world, id, child, RelationCause::Despawn);
+            queue.push_back(child);
+        }
+
+        if let Ok(mut entity) = world.get_entity_mut(id) {
+            if let Some(mut related) = entity.get_mut::<Related<N>>() {
+                related.clear();
+            }
+            entity.remove::<Related<N>>();
+            entity.despawn();
+        }
+    }
+}
+
+/// Reconciles the opposite side of the relation after the [`Related`]
+/// component of `a_id` was mutated in place, given its value before the
+/// mutation.
+///
+/// Only the entities that were actually added or removed are touched.
+pub(crate) fn reconcile<N: Relatable>(world: &mut World, a_id: Entity, old: &Related<N>) {
+    let new = world.get::<Related<N>>(a_id).cloned();
+    let a_points_to = |b_id: Entity| new.as_ref().is_some_and(|new| new.contains(b_id));
+    let b_points_to_a = |world: &World, b_id: Entity| {
+        world
+            .get::<Related<N::Opposite>>(b_id)
+            .is_some_and(|b| b.contains(a_id))
+    };
+
+    if !old.container.is_empty() {
+        for b_id in old.iter() {
+            if !a_points_to(b_id) && b_points_to_a(world, b_id) {
+                unlink::<N>(world, a_id, b_id, RelationCause::Mutation);
+            }
+        }
+    }
+
+    let Some(new) = new.as_ref() else {
+        return;
+    };
+
+    // The entities that were there before and after may have been rearranged.
+    if N::Container::ORDERED && !old.container.is_empty() {
+        let before = old.iter().filter(|&id| new.contains(id));
+        let after = new.iter().filter(|&id| old.contains(id));
+
+        if !before.eq(after) {
+            if let Some(mut events) = world.get_resource_mut::<Events<RelationReordered<N>>>() {
+                events.send(RelationReordered::new(a_id));
+            }
+        }
+    }
+
+    let mut despawned = Vec::new();
+    for b_id in new.iter() {
+        if old.contains(b_id) {
+            if old.data.get(&b_id) != new.data.get(&b_id) {
+                share_data::<N>(world, a_id, b_id);
+            }
+            if old.container.multiplicity(b_id) != new.container.multiplicity(b_id) {
+                share_multiplicity::<N>(world, a_id, b_id);
+            }
+            continue;
+        }
+
+        if world.get_entity(b_id).is_err() {
+            despawned.push(b_id);
+        } else if !b_points_to_a(world, b_id) {
+            link::<N>(world, a_id, b_id, RelationCause::Mutation);
+        }
+    }
+
+    dangling::<N>(world, a_id, &despawned);
+}
+
+/// Prunes `b_ids`, which no longer exist, from the [`Related`] component of
+/// `a_id` in one go, and reports each according to the [`DanglingPolicy`] of `N`.
+///
+/// If that leaves `a_id` without any links, the [`OrphanPolicy`] of `N` applies.
+pub(crate) fn dangling<N: Relatable>(world: &mut World, a_id: Entity, b_ids: &[Entity]) {
+    if b_ids.is_empty() {
+        return;
+    }
+
+    let mut edges = Vec::new();
+    let mut orphaned = false;
+    if let Ok(mut a) = world.get_entity_mut(a_id) {
+        if let Some(mut a_related) = a.get_mut::<Related<N>>() {
+            let pruned = b_ids.iter().copied().collect::<EntityHashSet>();
+            edges = b_ids
+                .iter()
+                .filter_map(|&b_id| a_related.edge(b_id))
+                .collect();
+            a_related.retain(|id| !pruned.contains(&id));
+
+            orphaned = a_related.container.is_empty();
+            if orphaned {
+                a.remove::<Related<N>>();
+            }
+        }
+    }
+
+    for edge in edges {
+        despawn_edge(world, edge);
+    }
+
+    for &b_id in b_ids {
+        match N::ON_DANGLING {
+            DanglingPolicy::Prune => {}
+            DanglingPolicy::Warn => warn!(
+                "{} of {a_id} refers to {b_id}, which no longer exists; pruning it",
+                type_name::<N>()
+            ),
+            DanglingPolicy::Report => report_error::<N>(
+                world,
+                RelationError::Dangling {
+                    entity: a_id,
+                    dangling: b_id,
+                },
+            ),
+        }
+    }
+
+    if orphaned {
+        orphan::<N>(world, a_id);
+    }
+}
+
+/// Adds `a_id` to the [`Related`] component of `b_id`, mutating it in place.
+///
+/// If this would displace an entity from `b_id`'s container (e.g. when it can
+/// only hold a single entity), the [`ConflictPolicy`] of `b_id`'s side decides
+/// which entity is unlinked from `b_id` in turn, or whether the new link is
+/// rejected or left one-sided. Containers that need a slot reject the link, as
+/// it doesn't say which slot to use.
+pub(crate) fn link<N: Relatable>(
+    world: &mut World,
+    a_id: Entity,
+    b_id: Entity,
+    cause: RelationCause,
+) {
+    if <N::Opposite as Relatable>::Container::SLOTTED {
+        let error = RelationError::NoSlot {
+            entity: a_id,
+            target: b_id,
+        };
+        return reject::<N>(world, a_id, b_id, error);
+    }
+
+    let data = edge_data::<N>(world, a_id, b_id);
+    let displaced = world
+        .get::<Related<N::Opposite>>(b_id)
+        .and_then(|b_related| b_related.container.displaced_by(a_id));
+
+    // Make room for this entity, unless the other entity refuses it.
+    let evicted = match (displaced, N::Opposite::ON_CONFLICT) {
+        (None, _) | (Some(_), ConflictPolicy::Steal) => displaced,
+        (Some(_), ConflictPolicy::Evict(choose)) => {
+            let b_related = world.get::<Related<N::Opposite>>(b_id);
+            let entities = b_related.iter().flat_map(|b| b.iter()).collect::<Vec<_>>();
+            let chosen = choose(world, b_id, &entities);
+            Some(chosen)
+                .filter(|c_id| entities.as_slice().contains(c_id))
+                .or(displaced)
+        }
+        (Some(_), ConflictPolicy::Reject) => {
+            let error = RelationError::Rejected {
+                entity: a_id,
+                target: b_id,
+            };
+            return reject::<N>(world, a_id, b_id, error);
+        }
+        (Some(_), ConflictPolicy::AllowAsymmetric) => return,
+    };
+
+    let Ok(mut b) = world.get_entity_mut(b_id) else {
+        return;
+    };
+
+    let data = if let Some(mut b_related) = b.get_mut::<Related<N::Opposite>>() {
+        // The other entity is already related to some entities, so add this entity to the list.
+        if let Some(c_id) = evicted {
+            b_related.remove(c_id);
+        }
+
+        b_related.push(a_id);
+        if let Some(data) = &data {
+            b_related.data.insert(a_id, data.clone());
+        }
+        b_related.data.get(&a_id).cloned()
+    } else {
+        // The other entity is not yet related to any entities, so relate it to this entity.
+        let mut b_related = Related::<N::Opposite>::from(a_id);
+        if let Some(data) = &data {
+            b_related.data.insert(a_id, data.clone());
+        }
+        let data = b_related.data.get(&a_id).cloned();
+        b.insert(b_related);
+        data
+    };
+
+    share_multiplicity::<N>(world, a_id, b_id);
+    reify::<N>(world, a_id, b_id);
+    send_event::<N>(world, RelationEvent::added, a_id, b_id, cause, data);
+    trigger(world, a_id, b_id, OnRelate::<N::Relation>::new);
+
+    if let Some(c_id) = evicted {
+        unlink::<N::Opposite>(world, b_id, c_id, RelationCause::Steal);
+    }
+}
+
+/// Returns the payload `a_id` holds for its edge to `b_id`, if any.
+fn edge_data<N: Relatable>(
+    world: &World,
+    a_id: Entity,
+    b_id: Entity,
+) -> Option<Arc<RelationData<N>>> {
+    world
+        .get::<Related<N>>(a_id)
+        .and_then(|a_related| a_related.data.get(&b_id).cloned())
+}
+
+/// Copies the payload `a_id` holds for its edge to `b_id` over to `b_id`'s
+/// side, so that both share it.
+fn share_data<N: Relatable>(world: &mut World, a_id: Entity, b_id: Entity) {
+    let data = edge_data::<N>(world, a_id, b_id);
+    let Some(mut b_related) = world.get_mut::<Related<N::Opposite>>(b_id) else {
+        return;
+    };
+
+    let shared = match (&data, b_related.data.get(&a_id)) {
+        (Some(data), Some(b_data)) => Arc::ptr_eq(data, b_data),
+        (None, None) => true,
+        _ => false,
+    };
+
+    if !shared {
+        match data {
+            Some(data) => b_related.data.insert(a_id, data),
+            None => b_related.data.remove(&a_id),
+        };
+    }
+}
+
+/// Copies the number of edges `a_id` has to `b_id` over to `b_id`'s side.
+fn share_multiplicity<N: Relatable>(world: &mut World, a_id: Entity, b_id: Entity) {
+    let Some(count) = world
+        .get::<Related<N>>(a_id)
+        .map(|a_related| a_related.container.multiplicity(b_id))
+        .filter(|&count| count > 0)
+    else {
+        return;
+    };
+
+    let Some(mut b_related) = world.get_mut::<Related<N::Opposite>>(b_id) else {
+        return;
+    };
+
+    if b_related.container.multiplicity(a_id) != count {
+        b_related.container.set_multiplicity(a_id, count);
+    }
+}
+
+/// Makes sure both sides of the link between `a_id` and `b_id` refer to the
+/// same edge entity, spawning one if neither does, when the relation is
+/// [`Relation::REIFIED`].
+fn reify<N: Relatable>(world: &mut World, a_id: Entity, b_id: Entity) {
+    if !N::Relation::REIFIED {
+        return;
+    }
+
+    let (a_edge, b_edge) = edges::<N>(world, a_id, b_id);
+    let edge = match (a_edge, b_edge) {
+        (Some(a_edge), Some(b_edge)) if a_edge == b_edge => return,
+        (Some(edge), _) | (None, Some(edge)) => edge,
+        (None, None) => reserve_edge::<N>(world, a_id, b_id),
+    };
+
+    if let Some(mut a_related) = world.get_mut::<Related<N>>(a_id) {
+        a_related.edges.insert(b_id, edge);
+    }
+    if let Some(mut b_related) = world.get_mut::<Related<N::Opposite>>(b_id) {
+        b_related.edges.insert(a_id, edge);
+    }
+
+    // Both sides had their own edge, so the one that lost out is no longer needed.
+    if let Some(b_edge) = b_edge.filter(|&b_edge| b_edge != edge) {
+        despawn_edge(world, b_edge);
+    }
+}
+
+/// Reserves an edge entity for the link between `a_id` and `b_id`, and queues
+/// inserting its [`Edge`] component.
+///
+/// Spawning would apply the queued commands in the middle of linking, so the
+/// edge entity only gets its component once the current command is done. By
+/// then the link may be gone, in which case the edge entity is despawned.
+fn reserve_edge<N: Relatable>(world: &mut World, a_id: Entity, b_id: Entity) -> Entity {
+    let edge = world.entities().reserve_entity();
+    let (source, target) = orient::<N>(a_id, b_id);
+
+    world.commands().queue(move |world: &mut World| {
+        let linked = world
+            .get::<Related<N>>(a_id)
+            .is_some_and(|a_related| a_related.edge(b_id) == Some(edge));
+
+        if let Ok(mut entity) = world.get_entity_mut(edge) {
+            if linked {
+                entity.insert(Edge::<N::Relation>::new(source, target));
+            } else {
+                entity.despawn();
             }
         }
     });
+
+    edge
+}
+
+/// Returns the edge entities both sides of the link between `a_id` and `b_id`
+/// refer to, if any.
+fn edges<N: Relatable>(
+    world: &World,
+    a_id: Entity,
+    b_id: Entity,
+) -> (Option<Entity>, Option<Entity>) {
+    let a_edge = world
+        .get::<Related<N>>(a_id)
+        .and_then(|a_related| a_related.edge(b_id));
+    let b_edge = world
+        .get::<Related<N::Opposite>>(b_id)
+        .and_then(|b_related| b_related.edge(a_id));
+    (a_edge, b_edge)
+}
+
+/// Despawns the given edge entity, if it still exists.
+pub(crate) fn despawn_edge(world: &mut World, edge: Entity) {
+    if let Ok(edge) = world.get_entity_mut(edge) {
+        edge.despawn();
+    }
+}
+
+/// Returns `a_id` and `b_id`, where `a_id` is on the `N` side, ordered from
+/// source to target.
+fn orient<N: Relatable>(a_id: Entity, b_id: Entity) -> (Entity, Entity) {
+    if TypeId::of::<N>() == TypeId::of::<<N::Relation as Relation>::Source>() {
+        (a_id, b_id)
+    } else {
+        (b_id, a_id)
+    }
+}
+
+/// Returns `true` if linking `a_id` to `b_id` would displace other entities
+/// from `b_id`'s container.
+pub(crate) fn displaces<N: Relatable>(world: &World, a_id: Entity, b_id: Entity) -> bool {
+    world
+        .get::<Related<N::Opposite>>(b_id)
+        .is_some_and(|b_related| b_related.container.displaced_by(a_id).is_some())
+}
+
+/// Removes `b_id` from the [`Related`] component of `a_id` after `b_id`
+/// refused the link, and reports the given error.
+///
+/// If that leaves `a_id` without any links, the [`OrphanPolicy`] of `N` applies.
+fn reject<N: Relatable>(world: &mut World, a_id: Entity, b_id: Entity, error: RelationError) {
+    let mut orphaned = false;
+    if let Ok(mut a) = world.get_entity_mut(a_id) {
+        if let Some(mut a_related) = a.get_mut::<Related<N>>() {
+            a_related.remove(b_id);
+
+            orphaned = a_related.container.is_empty();
+            if orphaned {
+                a.remove::<Related<N>>();
+            }
+        }
+    }
+
+    report_error::<N>(world, error);
+
+    if orphaned {
+        orphan::<N>(world, a_id);
+    }
+}
+
+/// Removes `a_id` from the [`Related`] component of `b_id`, mutating it in
+/// place and removing the component once it is empty, in which case the
+/// [`OrphanPolicy`] of `b_id`'s side applies.
+pub(crate) fn unlink<N: Relatable>(
+    world: &mut World,
+    a_id: Entity,
+    b_id: Entity,
+    cause: RelationCause,
+) {
+    if detach::<N>(world, a_id, b_id, cause) {
+        orphan::<N::Opposite>(world, b_id);
+    }
+}
+
+/// Like [`unlink`], but leaves the [`OrphanPolicy`] to the caller, returning
+/// whether `b_id` lost its last link.
+fn detach<N: Relatable>(
+    world: &mut World,
+    a_id: Entity,
+    b_id: Entity,
+    cause: RelationCause,
+) -> bool {
+    let Ok(mut b) = world.get_entity_mut(b_id) else {
+        return false;
+    };
+
+    let Some(mut b_related) = b.get_mut::<Related<N::Opposite>>() else {
+        return false;
+    };
+
+    // The other entity is related to some entities, so make sure this entity is removed from the list.
+    let data = b_related.data.get(&a_id).cloned();
+    let edge = b_related.edge(a_id);
+    b_related.remove(a_id);
+
+    // If the other entity is no longer related to any entities, remove the component.
+    let orphaned = b_related.container.is_empty();
+    if orphaned {
+        b.remove::<Related<N::Opposite>>();
+    }
+
+    send_event::<N>(world, RelationEvent::removed, a_id, b_id, cause, data);
+    trigger(world, a_id, b_id, OnUnrelate::<N::Relation>::new);
+
+    if let Some(edge) = edge {
+        despawn_edge(world, edge);
+    }
+
+    orphaned
+}
+
+/// Applies the [`OrphanPolicy`] of `N` to an entity that just lost its
+/// [`Related<N>`] component.
+fn orphan<N: Relatable>(world: &mut World, id: Entity) {
+    let Ok(mut entity) = world.get_entity_mut(id) else {
+        return;
+    };
+
+    match N::ON_ORPHAN {
+        OrphanPolicy::Keep => {}
+        OrphanPolicy::Despawn => entity.despawn(),
+        OrphanPolicy::Insert(f) => f(&mut entity),
+        OrphanPolicy::Trigger => world.trigger_targets(OnOrphan::<N>::new(), id),
+    }
+}
+
+/// Sends the [`RelationEvent`] built by `event` for the link between `a_id`,
+/// on the `N` side, and `b_id`, oriented from source to target.
+fn send_event<N: Relatable>(
+    world: &mut World,
+    event: fn(Entity, Entity, RelationCause) -> RelationEvent<N::Relation>,
+    a_id: Entity,
+    b_id: Entity,
+    cause: RelationCause,
+    data: Option<Arc<RelationData<N>>>,
+) {
+    let (source, target) = orient::<N>(a_id, b_id);
+    let event = event(source, target, cause).with_data(data);
+
+    let tick = world.change_tick();
+    if let Some(mut log) = world.get_resource_mut::<RelationChangeLog<N::Relation>>() {
+        let added = matches!(event, RelationEvent::Added { .. });
+        log.push(tick, added, event.source(), event.target());
+    }
+
+    if let Some(mut coalesced) = world.get_resource_mut::<CoalescedRelationEvents<N::Relation>>() {
+        coalesced.push(event);
+    } else if let Some(mut events) = world.get_resource_mut::<Events<RelationEvent<N::Relation>>>()
+    {
+        events.send(event);
+    }
+}
+
+/// Triggers the observer event built by `event` on both `a_id` and `b_id`,
+/// skipping entities that no longer exist.
+fn trigger<E: Event>(world: &mut World, a_id: Entity, b_id: Entity, event: impl Fn(Entity) -> E) {
+    for (target, other) in [(a_id, b_id), (b_id, a_id)] {
+        if world.get_entity(target).is_ok() {
+            world.trigger_targets(event(other), target);
+        }
+    }
 }
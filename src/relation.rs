@@ -1,3 +1,10 @@
+use core::fmt::Debug;
+
+use bevy_ecs::{
+    entity::Entity,
+    world::{EntityWorldMut, World},
+};
+
 use crate::container::EntityContainer;
 
 pub use evergreen_relations_macros::{Relatable, Relation};
@@ -7,12 +14,35 @@ pub use evergreen_relations_macros::{Relatable, Relation};
 /// Entity pointer data is stored in the [`Related`] component.
 ///
 /// [`Related`]: crate::related::Related
-pub trait Relation {
+pub trait Relation: 'static {
     /// The "source" node of the relation.
     type Source: Relatable<Relation = Self, Opposite = Self::Target>;
 
     /// The "target" node of the relation.
     type Target: Relatable<Relation = Self, Opposite = Self::Source>;
+
+    /// The payload stored on each edge of the relation, or `()` if there is none.
+    ///
+    /// The payload is stored once and shared by both sides of the edge.
+    type Data: PartialEq + Debug + Send + Sync + 'static;
+
+    /// Whether each link spawns an edge entity holding an [`Edge`] component
+    /// that refers to both endpoints.
+    ///
+    /// Arbitrary components can be added to the edge entity. Despawning it
+    /// removes the link, and removing the link despawns it.
+    ///
+    /// [`Edge`]: crate::edge::Edge
+    const REIFIED: bool = false;
+}
+
+/// One of the two sides of a [`Relation`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Side {
+    /// The [`Relation::Source`] side.
+    Source,
+    /// The [`Relation::Target`] side.
+    Target,
 }
 
 /// Trait for types that represent a node in a relationship.
@@ -29,4 +59,110 @@ pub trait Relatable: 'static {
 
     /// The container type that holds the related entities.
     type Container: EntityContainer;
+
+    /// What happens to the related entities when an entity holding this side
+    /// of the relation is despawned.
+    const ON_DESPAWN: DespawnPolicy = DespawnPolicy::Detach;
+
+    /// What happens to an entity when it loses its last related entity on this
+    /// side of the relation, because the other side unlinked it.
+    const ON_ORPHAN: OrphanPolicy = OrphanPolicy::Keep;
+
+    /// What happens when an entity on this side of the relation is found to
+    /// refer to an entity that no longer exists.
+    const ON_DANGLING: DanglingPolicy = DanglingPolicy::Prune;
+
+    /// What happens when another entity links to an entity on this side of the
+    /// relation, but the container cannot hold it without displacing others.
+    const ON_CONFLICT: ConflictPolicy = ConflictPolicy::Steal;
+}
+
+/// Determines what happens to the entities on the other side of a relation
+/// when an entity is despawned.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum DespawnPolicy {
+    /// Recursively despawn the related entities.
+    Cascade,
+    /// Remove the despawned entity from the related entities, leaving them alive.
+    #[default]
+    Detach,
+    /// Leave the related entities untouched.
+    Keep,
+}
+
+/// Determines how a new link is resolved when the container on the receiving
+/// side is full, e.g. a single-entity container that is already occupied, or a
+/// [`BoundedEntities`] container at capacity.
+///
+/// Conflicts are resolved in the order the writes are applied, so under
+/// [`ConflictPolicy::Steal`] the last write wins, and under
+/// [`ConflictPolicy::Reject`] the first one does.
+///
+/// The policy applies both to links made from the other side and to links
+/// made through [`RelationCommands::relate`] on the full entity itself. In the
+/// latter case, [`ConflictPolicy::AllowAsymmetric`] leaves the new link out.
+/// Mutating the [`Related`] component directly always displaces.
+///
+/// [`RelationCommands::relate`]: crate::commands::RelationCommands::relate
+/// [`Related`]: crate::related::Related
+/// [`BoundedEntities`]: crate::container::BoundedEntities
+#[derive(Clone, Copy, Debug, Default)]
+pub enum ConflictPolicy {
+    /// Unlink the entity displaced by the container, e.g. the oldest one,
+    /// sending [`RelationEvent::Removed`] for it with [`RelationCause::Steal`].
+    ///
+    /// [`RelationEvent::Removed`]: crate::event::RelationEvent::Removed
+    /// [`RelationCause::Steal`]: crate::event::RelationCause::Steal
+    #[default]
+    Steal,
+    /// Refuse the new link, removing it from the other side and sending a
+    /// [`RelationErrorEvent`].
+    ///
+    /// [`RelationErrorEvent`]: crate::event::RelationErrorEvent
+    Reject,
+    /// Keep the existing links, leaving the new link one-sided.
+    ///
+    /// Such links are reported as asymmetric by [`audit`].
+    ///
+    /// [`audit`]: crate::integrity::audit
+    AllowAsymmetric,
+    /// Like [`ConflictPolicy::Steal`], but unlink the entity chosen by the
+    /// given function instead, which is passed the entity on the receiving
+    /// side and the entities it is related to.
+    Evict(fn(&World, Entity, &[Entity]) -> Entity),
+}
+
+/// Determines how references to entities that no longer exist are handled.
+///
+/// The dangling reference is always pruned from the container.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum DanglingPolicy {
+    /// Silently prune the reference.
+    #[default]
+    Prune,
+    /// Prune the reference and log a warning.
+    Warn,
+    /// Prune the reference and send a [`RelationErrorEvent`].
+    ///
+    /// [`RelationErrorEvent`]: crate::event::RelationErrorEvent
+    Report,
+}
+
+/// Determines what happens to an entity when its [`Related`] component is
+/// removed because it no longer relates to any entities.
+///
+/// [`Related`]: crate::related::Related
+#[derive(Clone, Copy, Debug, Default)]
+pub enum OrphanPolicy {
+    /// Leave the entity as-is.
+    #[default]
+    Keep,
+    /// Despawn the entity.
+    Despawn,
+    /// Run the given function on the entity, e.g. to insert a marker component.
+    Insert(fn(&mut EntityWorldMut)),
+    /// Trigger an [`OnOrphan`] observer event targeted at the entity.
+    ///
+    /// [`OnOrphan`]: crate::event::OnOrphan
+    Trigger,
 }
@@ -0,0 +1,191 @@
+use std::collections::VecDeque;
+
+use bevy_ecs::{
+    entity::{Entity, EntityHashSet},
+    system::{Query, SystemParam},
+    world::World,
+};
+
+use crate::{related::Related, relation::Relation};
+
+/// Walks the hierarchy formed by a [`Relation`], going up through
+/// [`Related<R::Source>`] and down through [`Related<R::Target>`].
+///
+/// Every traversal visits each entity at most once, so a malformed graph with
+/// cycles can't make it loop forever.
+pub trait Traverse {
+    /// Returns the ancestors of the given entity, nearest first.
+    fn ancestors<R: Relation>(&self, entity: Entity) -> impl Iterator<Item = Entity> + '_;
+
+    /// Returns the descendants of the given entity in depth-first pre-order.
+    fn descendants_dfs<R: Relation>(&self, entity: Entity) -> impl Iterator<Item = Entity> + '_;
+
+    /// Returns the descendants of the given entity in breadth-first order.
+    fn descendants_bfs<R: Relation>(&self, entity: Entity) -> impl Iterator<Item = Entity> + '_;
+
+    /// Returns the other entities that share a parent with the given entity.
+    fn siblings<R: Relation>(&self, entity: Entity) -> impl Iterator<Item = Entity> + '_;
+
+    /// Returns the topmost ancestor of the given entity, following the first
+    /// parent at every level, or the entity itself if it has no parent.
+    ///
+    /// Returns `None` if the chain of parents loops.
+    fn root<R: Relation>(&self, entity: Entity) -> Option<Entity>;
+}
+
+impl Traverse for World {
+    fn ancestors<R: Relation>(&self, entity: Entity) -> impl Iterator<Item = Entity> + '_ {
+        walk(entity, |id| Lookup::<R>::parents(self, id), false)
+    }
+
+    fn descendants_dfs<R: Relation>(&self, entity: Entity) -> impl Iterator<Item = Entity> + '_ {
+        walk(entity, |id| Lookup::<R>::children(self, id), true)
+    }
+
+    fn descendants_bfs<R: Relation>(&self, entity: Entity) -> impl Iterator<Item = Entity> + '_ {
+        walk(entity, |id| Lookup::<R>::children(self, id), false)
+    }
+
+    fn siblings<R: Relation>(&self, entity: Entity) -> impl Iterator<Item = Entity> + '_ {
+        siblings::<R, _>(self, entity)
+    }
+
+    fn root<R: Relation>(&self, entity: Entity) -> Option<Entity> {
+        root::<R, _>(self, entity)
+    }
+}
+
+/// [`SystemParam`] for walking the hierarchy formed by a [`Relation`], like
+/// [`Traverse`] does for a [`World`].
+#[derive(SystemParam)]
+pub struct Hierarchy<'w, 's, R: Relation> {
+    sources: Query<'w, 's, &'static Related<<R as Relation>::Source>>,
+    targets: Query<'w, 's, &'static Related<<R as Relation>::Target>>,
+}
+
+impl<R: Relation> Hierarchy<'_, '_, R> {
+    /// Returns the ancestors of the given entity, nearest first.
+    pub fn ancestors(&self, entity: Entity) -> impl Iterator<Item = Entity> + '_ {
+        walk(entity, |id| self.parents(id), false)
+    }
+
+    /// Returns the descendants of the given entity in depth-first pre-order.
+    pub fn descendants_dfs(&self, entity: Entity) -> impl Iterator<Item = Entity> + '_ {
+        walk(entity, |id| self.children(id), true)
+    }
+
+    /// Returns the descendants of the given entity in breadth-first order.
+    pub fn descendants_bfs(&self, entity: Entity) -> impl Iterator<Item = Entity> + '_ {
+        walk(entity, |id| self.children(id), false)
+    }
+
+    /// Returns the other entities that share a parent with the given entity.
+    pub fn siblings(&self, entity: Entity) -> impl Iterator<Item = Entity> + '_ {
+        siblings::<R, _>(self, entity)
+    }
+
+    /// Returns the topmost ancestor of the given entity, following the first
+    /// parent at every level, or the entity itself if it has no parent.
+    ///
+    /// Returns `None` if the chain of parents loops.
+    pub fn root(&self, entity: Entity) -> Option<Entity> {
+        root::<R, _>(self, entity)
+    }
+}
+
+/// Looks up the neighbors of an entity in the hierarchy formed by `R`.
+trait Lookup<R: Relation> {
+    fn parents(&self, entity: Entity) -> impl Iterator<Item = Entity> + '_;
+
+    fn children(&self, entity: Entity) -> impl Iterator<Item = Entity> + '_;
+}
+
+impl<R: Relation> Lookup<R> for World {
+    fn parents(&self, entity: Entity) -> impl Iterator<Item = Entity> + '_ {
+        self.get::<Related<R::Source>>(entity)
+            .into_iter()
+            .flat_map(|related| related.iter())
+    }
+
+    fn children(&self, entity: Entity) -> impl Iterator<Item = Entity> + '_ {
+        self.get::<Related<R::Target>>(entity)
+            .into_iter()
+            .flat_map(|related| related.iter())
+    }
+}
+
+impl<R: Relation> Lookup<R> for Hierarchy<'_, '_, R> {
+    fn parents(&self, entity: Entity) -> impl Iterator<Item = Entity> + '_ {
+        self.sources
+            .get(entity)
+            .into_iter()
+            .flat_map(|related| related.iter())
+    }
+
+    fn children(&self, entity: Entity) -> impl Iterator<Item = Entity> + '_ {
+        self.targets
+            .get(entity)
+            .into_iter()
+            .flat_map(|related| related.iter())
+    }
+}
+
+/// Visits every entity reachable from `entity` through `step`, excluding
+/// `entity` itself, either depth- or breadth-first.
+fn walk<'a, I>(
+    entity: Entity,
+    step: impl Fn(Entity) -> I + 'a,
+    depth_first: bool,
+) -> impl Iterator<Item = Entity> + 'a
+where
+    I: Iterator<Item = Entity>,
+{
+    let mut visited = EntityHashSet::default();
+    visited.insert(entity);
+    let mut pending = step(entity).collect::<VecDeque<_>>();
+
+    std::iter::from_fn(move || loop {
+        let id = pending.pop_front()?;
+        if !visited.insert(id) {
+            continue;
+        }
+
+        if depth_first {
+            let next = step(id).collect::<Vec<_>>();
+            for next_id in next.into_iter().rev() {
+                pending.push_front(next_id);
+            }
+        } else {
+            pending.extend(step(id));
+        }
+
+        return Some(id);
+    })
+}
+
+fn siblings<R: Relation, L: Lookup<R>>(
+    lookup: &L,
+    entity: Entity,
+) -> impl Iterator<Item = Entity> + '_ {
+    let mut seen = EntityHashSet::default();
+    seen.insert(entity);
+
+    lookup
+        .parents(entity)
+        .flat_map(|parent| lookup.children(parent))
+        .filter(move |&id| seen.insert(id))
+}
+
+fn root<R: Relation, L: Lookup<R>>(lookup: &L, entity: Entity) -> Option<Entity> {
+    let mut visited = EntityHashSet::default();
+    let mut current = entity;
+
+    while visited.insert(current) {
+        match lookup.parents(current).next() {
+            Some(parent) => current = parent,
+            None => return Some(current),
+        }
+    }
+
+    None
+}
@@ -0,0 +1,64 @@
+use bevy_ecs::{entity::Entity, world::World};
+use evergreen_relations::{container::EntityBitSet, prelude::*};
+
+/// A directed 1:N relationship between a faction and its many units.
+#[derive(Relation)]
+#[relation(source = ServesIn, target = Commands)]
+pub struct Allegiance;
+
+pub type Faction = Related<ServesIn>;
+
+#[derive(Relatable)]
+#[relatable(Option<Entity> in Allegiance, opposite = Commands)]
+pub struct ServesIn;
+
+pub type Units = Related<Commands>;
+
+#[derive(Relatable)]
+#[relatable(EntityBitSet in Allegiance, opposite = ServesIn)]
+pub struct Commands;
+
+#[test]
+fn high_fan_out() {
+    let mut world = World::new();
+
+    let a = world.spawn_empty().id();
+    let units = (0..20_000)
+        .map(|_| world.spawn(Faction::new(a)).id())
+        .collect::<Vec<_>>();
+    world.flush();
+
+    let commanded = world.get::<Units>(a).unwrap();
+    assert_eq!(commanded.len(), 20_000);
+    assert!(units
+        .as_slice()
+        .iter()
+        .all(|&unit| commanded.contains(unit)));
+    assert_eq!(commanded.iter().collect::<Vec<_>>(), units);
+
+    for &unit in &units[..10_000] {
+        world.despawn(unit);
+    }
+    world.flush();
+
+    let commanded = world.get::<Units>(a).unwrap();
+    assert_eq!(commanded.len(), 10_000);
+    assert_eq!(commanded.iter().collect::<Vec<_>>(), &units[10_000..]);
+}
+
+#[test]
+fn reused_index() {
+    let mut world = World::new();
+
+    let b = world.spawn_empty().id();
+    let units = Units::from_iter([b]);
+    world.despawn(b);
+
+    // The new entity reuses the index of the despawned one.
+    let c = world.spawn_empty().id();
+    assert_eq!(c.index(), b.index());
+
+    assert!(units.contains(b));
+    assert!(!units.contains(c));
+    assert_eq!(units.iter().collect::<Vec<_>>(), vec![b]);
+}
@@ -1,5 +1,19 @@
-use bevy_ecs::{entity::Entity, world::World};
-use evergreen_relations::prelude::*;
+use bevy_ecs::{
+    component::Component,
+    entity::Entity,
+    event::Events,
+    observer::Trigger,
+    query::Without,
+    system::{ResMut, Resource, SystemState},
+    world::World,
+};
+use evergreen_relations::{
+    changes::RelationChangeLog,
+    event::{OnOrphan, OnRelate, OnUnrelate},
+    integrity::{audit, repair, AssertIntegrity, IntegrityReport, Link},
+    prelude::*,
+    relation::Side,
+};
 use smallvec::SmallVec;
 
 /// A directed 1:N relationship between entities.
@@ -21,6 +35,30 @@ pub struct ParentOf;
 
 pub type Lineage = EitherRelated<Family>;
 
+/// A directed 1:N relationship where despawning an ancestor despawns all of its
+/// descendants, and entities left without ancestors or descendants are
+/// announced.
+#[derive(Relation)]
+#[relation(source = DescendsFrom, target = AncestorOf)]
+pub struct Bloodline;
+
+pub type Ancestor = Related<DescendsFrom>;
+
+#[derive(Relatable)]
+#[relatable(Entity in Bloodline, opposite = AncestorOf, on_orphan = trigger)]
+pub struct DescendsFrom;
+
+pub type Descendants = Related<AncestorOf>;
+
+#[derive(Relatable)]
+#[relatable(
+    SmallVec<[Entity; 8]> in Bloodline,
+    opposite = DescendsFrom,
+    on_despawn = cascade,
+    on_orphan = trigger,
+)]
+pub struct AncestorOf;
+
 #[test]
 fn add_remove() {
     let mut world = World::new();
@@ -92,3 +130,570 @@ fn both_related() {
     assert_eq!(c_related.source, Some(&Parent::new(b)));
     assert_eq!(c_related.target, None);
 }
+
+#[test]
+fn relation_commands() {
+    let mut world = World::new();
+
+    let a = world.spawn_empty().id();
+    let b = world.spawn_empty().id();
+    let c = world.spawn_empty().id();
+
+    world.entity_mut(a).relate::<ParentOf>(b);
+    world.commands().entity(c).relate::<ChildOf>(a);
+    world.flush();
+
+    assert_eq!(
+        world
+            .get::<Children>(a)
+            .map(|children| children.iter().collect::<Vec<_>>()),
+        Some(vec![b, c])
+    );
+    assert_eq!(world.get::<Parent>(b), Some(&Parent::new(a)));
+    assert_eq!(world.get::<Parent>(c), Some(&Parent::new(a)));
+
+    world.entity_mut(a).unrelate::<ParentOf>(b);
+    world.flush();
+
+    assert_eq!(
+        world
+            .get::<Children>(a)
+            .map(|children| children.iter().collect::<Vec<_>>()),
+        Some(vec![c])
+    );
+    assert_eq!(world.get::<Parent>(b), None);
+
+    world.commands().entity(a).set_related::<ParentOf>([b]);
+    world.flush();
+
+    assert_eq!(
+        world
+            .get::<Children>(a)
+            .map(|children| children.iter().collect::<Vec<_>>()),
+        Some(vec![b])
+    );
+    assert_eq!(world.get::<Parent>(b), Some(&Parent::new(a)));
+    assert_eq!(world.get::<Parent>(c), None);
+
+    world.entity_mut(a).unrelate_all::<ParentOf>();
+    world.flush();
+
+    assert_eq!(world.get::<Children>(a), None);
+    assert_eq!(world.get::<Parent>(b), None);
+}
+
+#[test]
+fn despawn_cascade() {
+    let mut world = World::new();
+
+    let root = world.spawn_empty().id();
+    let mut leaf = root;
+    for _ in 0..10_000 {
+        leaf = world.spawn(Ancestor::new(leaf)).id();
+    }
+    let sibling = world.spawn(Ancestor::new(root)).id();
+
+    world.flush();
+
+    world.despawn(root);
+    world.flush();
+
+    assert!(world.get_entity(leaf).is_err());
+    assert!(world.get_entity(sibling).is_err());
+    assert_eq!(world.entities().len(), 0);
+}
+
+#[test]
+fn despawn_cascade_cycle() {
+    let mut world = World::new();
+
+    let a = world.spawn_empty().id();
+    let b = world.spawn(Ancestor::new(a)).id();
+    world.entity_mut(a).insert(Ancestor::new(b));
+    let c = world.spawn_empty().id();
+
+    world.flush();
+
+    world.despawn(a);
+    world.flush();
+
+    assert!(world.get_entity(b).is_err());
+    assert!(world.get_entity(c).is_ok());
+}
+
+#[test]
+fn despawn_cascade_events() {
+    let mut world = World::new();
+    world.init_resource::<Events<RelationEvent<Bloodline>>>();
+
+    let a = world.spawn_empty().id();
+    let b = world.spawn(Ancestor::new(a)).id();
+    let c = world.spawn(Ancestor::new(b)).id();
+    world.flush();
+    world
+        .resource_mut::<Events<RelationEvent<Bloodline>>>()
+        .clear();
+
+    world.despawn(a);
+    world.flush();
+
+    // Every link broken by the cascade is reported as despawned.
+    assert_eq!(
+        world
+            .resource_mut::<Events<RelationEvent<Bloodline>>>()
+            .drain()
+            .collect::<Vec<_>>(),
+        vec![
+            RelationEvent::removed(b, a, RelationCause::Despawn),
+            RelationEvent::removed(c, b, RelationCause::Despawn),
+        ]
+    );
+    assert!(world.get_entity(b).is_err());
+    assert!(world.get_entity(c).is_err());
+}
+
+#[test]
+fn despawn_child_detaches() {
+    let mut world = World::new();
+
+    let a = world.spawn_empty().id();
+    let b = world.spawn(Parent::new(a)).id();
+    let c = world.spawn(Parent::new(a)).id();
+
+    world.flush();
+
+    world.despawn(b);
+    world.flush();
+
+    assert_eq!(
+        world
+            .get::<Children>(a)
+            .map(|children| children.iter().collect::<Vec<_>>()),
+        Some(vec![c])
+    );
+}
+
+#[test]
+fn childless_trigger() {
+    #[derive(Resource, Default)]
+    struct Childless(Vec<Entity>);
+
+    let mut world = World::new();
+    world.init_resource::<Childless>();
+    world.add_observer(
+        |trigger: Trigger<OnOrphan<AncestorOf>>, mut childless: ResMut<Childless>| {
+            childless.0.push(trigger.entity());
+        },
+    );
+
+    let a = world.spawn_empty().id();
+    let b = world.spawn(Ancestor::new(a)).id();
+    let c = world.spawn(Ancestor::new(a)).id();
+    world.flush();
+
+    world.entity_mut(b).remove::<Ancestor>();
+    world.flush();
+
+    assert!(world.resource::<Childless>().0.is_empty());
+
+    world.despawn(c);
+    world.flush();
+
+    assert_eq!(world.resource::<Childless>().0, vec![a]);
+}
+
+#[test]
+fn despawn_cascade_no_orphans() {
+    #[derive(Resource, Default)]
+    struct Orphans(Vec<Entity>);
+
+    let mut world = World::new();
+    world.init_resource::<Orphans>();
+    world.add_observer(
+        |trigger: Trigger<OnOrphan<DescendsFrom>>, mut orphans: ResMut<Orphans>| {
+            orphans.0.push(trigger.entity());
+        },
+    );
+
+    let a = world.spawn_empty().id();
+    let b = world.spawn(Ancestor::new(a)).id();
+    world.spawn(Ancestor::new(b));
+    world.flush();
+
+    // The descendants are despawned along with their ancestor, so they are
+    // never announced as orphans.
+    world.despawn(a);
+    world.flush();
+
+    assert!(world.resource::<Orphans>().0.is_empty());
+}
+
+#[test]
+fn integrity() {
+    let mut world = World::new();
+
+    let a = world.spawn_empty().id();
+    let b = world.spawn(Parent::new(a)).id();
+    let c = world.spawn_empty().id();
+    let d = world.spawn(Parent::new(a)).id();
+
+    world.flush();
+
+    assert!(audit::<Family>(&world).is_empty());
+
+    // Mutating the components directly bypasses the hooks.
+    world.get_mut::<Children>(a).unwrap().push(c);
+    world.get_mut::<Parent>(d).unwrap().remove(a);
+    world.get_mut::<Parent>(d).unwrap().push(c);
+
+    let report = IntegrityReport {
+        asymmetric: vec![
+            Link {
+                side: Side::Source,
+                from: d,
+                to: c,
+            },
+            Link {
+                side: Side::Target,
+                from: a,
+                to: c,
+            },
+            Link {
+                side: Side::Target,
+                from: a,
+                to: d,
+            },
+        ],
+        ..Default::default()
+    };
+    let mut audited = audit::<Family>(&world);
+    audited
+        .asymmetric
+        .sort_by_key(|link| (link.side == Side::Target, link.to));
+    assert_eq!(audited, report);
+
+    repair::<Family>(&mut world, Side::Source);
+    world.flush();
+
+    assert!(audit::<Family>(&world).is_empty());
+    assert_eq!(
+        world
+            .get::<Children>(a)
+            .map(|children| children.iter().collect::<Vec<_>>()),
+        Some(vec![b])
+    );
+    assert_eq!(
+        world
+            .get::<Children>(c)
+            .map(|children| children.iter().collect::<Vec<_>>()),
+        Some(vec![d])
+    );
+}
+
+#[test]
+fn repair_duplicates() {
+    let mut world = World::new();
+
+    let a = world.spawn_empty().id();
+    let b = world.spawn(Parent::new(a)).id();
+    let c = world.spawn(Parent::new(a)).id();
+    world.flush();
+
+    // Replacing the component in place bypasses the hooks.
+    *world.get_mut::<Children>(a).unwrap() = Children::new(SmallVec::from_slice(&[b, c, b]));
+
+    assert_eq!(
+        audit::<Family>(&world),
+        IntegrityReport {
+            duplicates: vec![Link {
+                side: Side::Target,
+                from: a,
+                to: b,
+            }],
+            ..Default::default()
+        }
+    );
+
+    repair::<Family>(&mut world, Side::Source);
+    world.flush();
+
+    // The first copy is kept where it was.
+    assert!(audit::<Family>(&world).is_empty());
+    assert_eq!(
+        world
+            .get::<Children>(a)
+            .map(|children| children.iter().collect::<Vec<_>>()),
+        Some(vec![b, c])
+    );
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "relation `family::Family` is inconsistent")]
+fn assert_integrity_after_flush() {
+    let mut world = World::new();
+    world.init_resource::<AssertIntegrity<Family>>();
+
+    let a = world.spawn_empty().id();
+    let b = world.spawn(Parent::new(a)).id();
+    let c = world.spawn(Parent::new(a)).id();
+    world.entity_mut(b).insert(Parent::new(c));
+    world.entity_mut(c).remove::<Parent>();
+    world.flush();
+
+    // The hooks keep the relation consistent, so nothing is reported so far.
+    assert_eq!(world.get::<Children>(c), Some(&Children::from_iter([b])));
+
+    // Mutating the components directly bypasses the hooks, which is caught by the next flush.
+    world.get_mut::<Children>(c).unwrap().push(a);
+    world.spawn(Parent::new(c));
+    world.flush();
+}
+
+#[test]
+fn observers() {
+    #[derive(Resource, Default)]
+    struct Log(Vec<(&'static str, Entity, Entity)>);
+
+    let mut world = World::new();
+    world.init_resource::<Log>();
+
+    let a = world.spawn_empty().id();
+    world
+        .entity_mut(a)
+        .observe(|trigger: Trigger<OnRelate<Family>>, mut log: ResMut<Log>| {
+            log.0.push(("relate", trigger.entity(), trigger.other));
+        })
+        .observe(
+            |trigger: Trigger<OnUnrelate<Family>>, mut log: ResMut<Log>| {
+                log.0.push(("unrelate", trigger.entity(), trigger.other));
+            },
+        );
+
+    let b = world.spawn(Parent::new(a)).id();
+    world.flush();
+
+    assert_eq!(world.resource::<Log>().0, vec![("relate", a, b)]);
+
+    world.entity_mut(b).remove::<Parent>();
+    world.flush();
+
+    assert_eq!(
+        world.resource::<Log>().0,
+        vec![("relate", a, b), ("unrelate", a, b)]
+    );
+}
+
+#[test]
+fn events() {
+    let mut world = World::new();
+
+    world.init_resource::<Events<RelationEvent<Family>>>();
+
+    let a = world.spawn_empty().id();
+    let b = world.spawn(Parent::new(a)).id();
+    let c = world.spawn_empty().id();
+    world.entity_mut(a).relate::<ParentOf>(c);
+    world.flush();
+
+    // Events are oriented from child to parent, regardless of the side that was changed.
+    assert_eq!(
+        world
+            .resource_mut::<Events<RelationEvent<Family>>>()
+            .drain()
+            .collect::<Vec<_>>(),
+        vec![
+            RelationEvent::added(b, a, RelationCause::Insert),
+            RelationEvent::added(c, a, RelationCause::Mutation),
+        ]
+    );
+
+    world.despawn(b);
+    world.flush();
+
+    assert_eq!(
+        world
+            .resource_mut::<Events<RelationEvent<Family>>>()
+            .drain()
+            .collect::<Vec<_>>(),
+        vec![RelationEvent::removed(b, a, RelationCause::Despawn)]
+    );
+}
+
+#[test]
+fn changes() {
+    #[derive(Resource, Default)]
+    struct Seen(Vec<(Entity, Entity)>, Vec<(Entity, Entity)>);
+
+    fn record(changes: RelationChanges<Family>, mut seen: ResMut<Seen>) {
+        seen.0 = changes.added().collect();
+        seen.1 = changes.removed().collect();
+    }
+
+    let mut world = World::new();
+    world.init_resource::<Seen>();
+    let system = world.register_system(record);
+    world.run_system(system).unwrap();
+
+    let a = world.spawn_empty().id();
+    let b = world.spawn(Parent::new(a)).id();
+    let c = world.spawn_empty().id();
+    world.entity_mut(a).relate::<ParentOf>(c);
+    world.flush();
+
+    world.run_system(system).unwrap();
+    assert_eq!(world.resource::<Seen>().0, vec![(b, a), (c, a)]);
+    assert!(world.resource::<Seen>().1.is_empty());
+
+    // Changes are only reported once per system.
+    world.run_system(system).unwrap();
+    assert!(world.resource::<Seen>().0.is_empty());
+
+    world.despawn(b);
+    world.flush();
+
+    world.run_system(system).unwrap();
+    assert!(world.resource::<Seen>().0.is_empty());
+    assert_eq!(world.resource::<Seen>().1, vec![(b, a)]);
+}
+
+#[test]
+fn traversal() {
+    let mut world = World::new();
+
+    //     a
+    //    / \
+    //   b   c
+    //  / \   \
+    // d   e   f
+    let a = world.spawn_empty().id();
+    let b = world.spawn(Parent::new(a)).id();
+    let c = world.spawn(Parent::new(a)).id();
+    let d = world.spawn(Parent::new(b)).id();
+    let e = world.spawn(Parent::new(b)).id();
+    let f = world.spawn(Parent::new(c)).id();
+    world.flush();
+
+    assert_eq!(world.ancestors::<Family>(e).collect::<Vec<_>>(), vec![b, a]);
+    assert_eq!(
+        world.descendants_dfs::<Family>(a).collect::<Vec<_>>(),
+        vec![b, d, e, c, f]
+    );
+    assert_eq!(
+        world.descendants_bfs::<Family>(a).collect::<Vec<_>>(),
+        vec![b, c, d, e, f]
+    );
+    assert_eq!(world.siblings::<Family>(d).collect::<Vec<_>>(), vec![e]);
+    assert_eq!(world.siblings::<Family>(a).count(), 0);
+    assert_eq!(world.root::<Family>(f), Some(a));
+    assert_eq!(world.root::<Family>(a), Some(a));
+
+    // The system param walks the same hierarchy.
+    let mut state = SystemState::<Hierarchy<Family>>::new(&mut world);
+    let hierarchy = state.get(&world);
+
+    assert_eq!(hierarchy.ancestors(e).collect::<Vec<_>>(), vec![b, a]);
+    assert_eq!(
+        hierarchy.descendants_dfs(a).collect::<Vec<_>>(),
+        vec![b, d, e, c, f]
+    );
+    assert_eq!(
+        hierarchy.descendants_bfs(a).collect::<Vec<_>>(),
+        vec![b, c, d, e, f]
+    );
+    assert_eq!(hierarchy.siblings(e).collect::<Vec<_>>(), vec![d]);
+    assert_eq!(hierarchy.root(d), Some(a));
+}
+
+#[test]
+fn traversal_cycle() {
+    let mut world = World::new();
+
+    let a = world.spawn_empty().id();
+    let b = world.spawn(Parent::new(a)).id();
+    let c = world.spawn(Parent::new(b)).id();
+    world.entity_mut(a).insert(Parent::new(c));
+    world.flush();
+
+    // Each entity is visited once, even though the parents loop.
+    assert_eq!(world.ancestors::<Family>(a).collect::<Vec<_>>(), vec![c, b]);
+    assert_eq!(
+        world.descendants_dfs::<Family>(a).collect::<Vec<_>>(),
+        vec![b, c]
+    );
+    assert_eq!(
+        world.descendants_bfs::<Family>(a).collect::<Vec<_>>(),
+        vec![b, c]
+    );
+    assert_eq!(world.root::<Family>(a), None);
+}
+
+#[test]
+fn related_query() {
+    #[derive(Component, Debug, PartialEq)]
+    struct Age(u32);
+
+    #[derive(Component)]
+    struct Adopted;
+
+    let mut world = World::new();
+
+    let a = world.spawn(Age(40)).id();
+    let b = world.spawn((Age(10), Parent::new(a))).id();
+    let c = world.spawn((Age(8), Parent::new(a), Adopted)).id();
+    let d = world.spawn(Parent::new(a)).id();
+    world.flush();
+
+    // Read-only queries can hand out every related item at once.
+    let mut state = SystemState::<RelatedQuery<ParentOf, &Age>>::new(&mut world);
+    let children = state.get(&world);
+
+    assert_eq!(
+        children.iter(a).collect::<Vec<_>>(),
+        vec![(b, &Age(10)), (c, &Age(8))]
+    );
+    assert_eq!(children.get(a, c), Some(&Age(8)));
+    assert_eq!(children.get(a, d), None);
+    assert_eq!(children.get(b, a), None);
+
+    let mut state =
+        SystemState::<RelatedQuery<ParentOf, &mut Age, Without<Adopted>>>::new(&mut world);
+    let mut children = state.get_mut(&mut world);
+
+    children.for_each_mut(a, |_, mut age| age.0 += 1);
+    if let Some(mut age) = children.get_mut(a, b) {
+        age.0 += 1;
+    }
+    assert!(children.get_mut(a, c).is_none());
+
+    assert_eq!(world.get::<Age>(b), Some(&Age(12)));
+    assert_eq!(world.get::<Age>(c), Some(&Age(8)));
+
+    // Looking up the parent goes through the other side.
+    let mut state = SystemState::<RelatedQuery<ChildOf, &Age>>::new(&mut world);
+    let parent = state.get(&world);
+
+    assert_eq!(parent.iter(d).collect::<Vec<_>>(), vec![(a, &Age(40))]);
+}
+
+#[test]
+fn changes_dropped_reader() {
+    let mut world = World::new();
+
+    let mut state = SystemState::<RelationChanges<Family>>::new(&mut world);
+    state.get(&world);
+    drop(state);
+
+    let a = world.spawn_empty().id();
+    for _ in 0..10 {
+        let b = world.spawn(Parent::new(a)).id();
+        world.flush();
+        world.increment_change_tick();
+        world.despawn(b);
+        world.flush();
+        world.increment_change_tick();
+    }
+
+    // Nothing is left to read the changes, so they aren't kept around.
+    assert!(world.resource::<RelationChangeLog<Family>>().len() <= 1);
+}
@@ -0,0 +1,112 @@
+use bevy_ecs::{entity::Entity, event::Events, world::World};
+use evergreen_relations::{container::MultiEntities, prelude::*};
+use smallvec::SmallVec;
+
+/// A directed N:M multigraph of favors owed between entities.
+#[derive(Relation)]
+#[relation(source = Owes, target = OwedBy)]
+pub struct Favor;
+
+pub type Debts = Related<Owes>;
+
+#[derive(Relatable)]
+#[relatable(MultiEntities in Favor, opposite = OwedBy)]
+pub struct Owes;
+
+pub type Credits = Related<OwedBy>;
+
+#[derive(Relatable)]
+#[relatable(MultiEntities in Favor, opposite = Owes)]
+pub struct OwedBy;
+
+/// A directed N:M relationship backed by a set-like container.
+#[derive(Relation)]
+#[relation(source = Follows, target = FollowedBy)]
+pub struct Following;
+
+#[derive(Relatable)]
+#[relatable(SmallVec<[Entity; 4]> in Following, opposite = FollowedBy)]
+pub struct Follows;
+
+#[derive(Relatable)]
+#[relatable(Vec<Entity> in Following, opposite = Follows)]
+pub struct FollowedBy;
+
+fn multiplicity(world: &World, a: Entity, b: Entity) -> (usize, usize) {
+    (
+        world
+            .get::<Debts>(a)
+            .map_or(0, |debts| debts.multiplicity(b)),
+        world
+            .get::<Credits>(b)
+            .map_or(0, |credits| credits.multiplicity(a)),
+    )
+}
+
+#[test]
+fn multiplicity_mirrored() {
+    let mut world = World::new();
+    world.init_resource::<Events<RelationEvent<Favor>>>();
+
+    let a = world.spawn_empty().id();
+    let b = world.spawn(Credits::new(MultiEntities::new(a))).id();
+    world.flush();
+
+    world.entity_mut(a).modify_related::<Owes>(move |debts| {
+        debts.add_edge(b);
+        debts.add_edge(b);
+    });
+    world.flush();
+
+    assert_eq!(multiplicity(&world, a, b), (3, 3));
+
+    world
+        .entity_mut(b)
+        .modify_related::<OwedBy>(move |credits| {
+            credits.remove_one_edge(a);
+        });
+    world.flush();
+
+    assert_eq!(multiplicity(&world, a, b), (2, 2));
+
+    // The edge is only reported once, when it is first added and last removed.
+    world.entity_mut(a).modify_related::<Owes>(move |debts| {
+        debts.remove_one_edge(b);
+        debts.remove_one_edge(b);
+    });
+    world.flush();
+
+    assert_eq!(multiplicity(&world, a, b), (0, 0));
+    assert_eq!(
+        world
+            .resource_mut::<Events<RelationEvent<Favor>>>()
+            .drain()
+            .collect::<Vec<_>>(),
+        vec![
+            RelationEvent::added(a, b, RelationCause::Insert),
+            RelationEvent::removed(a, b, RelationCause::Mutation),
+        ]
+    );
+}
+
+#[test]
+fn no_duplicates() {
+    let mut world = World::new();
+
+    let a = world.spawn_empty().id();
+    let b = world.spawn(Related::<Follows>::from_iter([a, a])).id();
+    world.entity_mut(b).relate::<Follows>(a);
+    world.flush();
+
+    assert_eq!(world.get::<Related<Follows>>(b).map(Related::len), Some(1));
+    assert_eq!(
+        world.get::<Related<FollowedBy>>(a).map(Related::len),
+        Some(1)
+    );
+
+    world.entity_mut(b).unrelate::<Follows>(a);
+    world.flush();
+
+    assert_eq!(world.get::<Related<Follows>>(b), None);
+    assert_eq!(world.get::<Related<FollowedBy>>(a), None);
+}
@@ -1,5 +1,11 @@
-use bevy_ecs::{entity::Entity, world::World};
+use std::sync::Arc;
+
+use bevy_ecs::{entity::Entity, event::Events, world::World};
 use evergreen_relations::{
+    commands::RelationCommands,
+    error::RelationError,
+    event::{RelationCause, RelationErrorEvent, RelationEvent},
+    integrity::prune_dangling,
     related::Related,
     relation::{Relatable, Relation},
 };
@@ -16,6 +22,38 @@ pub type Friend = Related<FriendOf>;
 #[relatable(SmallVec<[Entity; 8]> in Friendship, opposite = Self)]
 pub struct FriendOf;
 
+/// An undirected N:M relationship that remembers when it started.
+#[derive(Relation)]
+#[relation(source = FellowOf, target = FellowOf, data = FellowsSince)]
+pub struct Fellowship;
+
+/// The year a fellowship started.
+#[derive(Debug, PartialEq)]
+pub struct FellowsSince(u32);
+
+pub type Fellows = Related<FellowOf>;
+
+#[derive(Relatable)]
+#[relatable(SmallVec<[Entity; 8]> in Fellowship, opposite = Self)]
+pub struct FellowOf;
+
+/// An undirected N:M relationship that outlives despawned entities, until
+/// their dangling references are pruned and reported.
+#[derive(Relation)]
+#[relation(source = AcquaintedWith, target = AcquaintedWith)]
+pub struct Acquaintance;
+
+pub type Acquaintances = Related<AcquaintedWith>;
+
+#[derive(Relatable)]
+#[relatable(
+    SmallVec<[Entity; 8]> in Acquaintance,
+    opposite = Self,
+    on_despawn = keep,
+    on_dangling = report,
+)]
+pub struct AcquaintedWith;
+
 #[test]
 fn add_remove() {
     let mut world = World::new();
@@ -38,3 +76,164 @@ fn add_remove() {
     assert_eq!(world.get::<Friend>(b), None);
     assert_eq!(world.get::<Friend>(c), Some(&Friend::from_iter([a])));
 }
+
+#[test]
+fn modify_in_place() {
+    let mut world = World::new();
+
+    let a = world.spawn_empty().id();
+    let b = world.spawn_empty().id();
+    let c = world.spawn_empty().id();
+    let d = world.spawn(Friend::from_iter([a, b, c])).id();
+
+    world.flush();
+    world.init_resource::<Events<RelationEvent<Friendship>>>();
+
+    world
+        .entity_mut(d)
+        .modify_related::<FriendOf>(move |friends| {
+            friends.retain(|id| id != b);
+            friends.push(a);
+        });
+    world.flush();
+
+    assert_eq!(world.get::<Friend>(d), Some(&Friend::from_iter([a, c])));
+    assert_eq!(world.get::<Friend>(d).map(Friend::len), Some(2));
+    assert_eq!(world.get::<Friend>(a), Some(&Friend::from_iter([d])));
+    assert_eq!(world.get::<Friend>(b), None);
+    assert_eq!(world.get::<Friend>(c), Some(&Friend::from_iter([d])));
+
+    // Only the entity that actually changed is reported.
+    assert_eq!(
+        world
+            .resource_mut::<Events<RelationEvent<Friendship>>>()
+            .drain()
+            .collect::<Vec<_>>(),
+        vec![RelationEvent::removed(d, b, RelationCause::Mutation)]
+    );
+
+    world
+        .entity_mut(d)
+        .modify_related::<FriendOf>(|friends| friends.clear());
+    world.flush();
+
+    assert_eq!(world.get::<Friend>(a), None);
+    assert_eq!(world.get::<Friend>(c), None);
+    assert_eq!(world.get::<Friend>(d), None);
+}
+
+#[test]
+fn dangling() {
+    let mut world = World::new();
+    world.init_resource::<Events<RelationErrorEvent<Acquaintance>>>();
+
+    let a = world.spawn_empty().id();
+    let b = world.spawn_empty().id();
+    world.despawn(a);
+    let c = world.spawn(Acquaintances::from_iter([a, b])).id();
+
+    world.flush();
+
+    assert_eq!(
+        world.get::<Acquaintances>(b),
+        Some(&Acquaintances::from_iter([c]))
+    );
+    assert_eq!(
+        world.get::<Acquaintances>(c),
+        Some(&Acquaintances::from_iter([b]))
+    );
+    assert_eq!(
+        world
+            .resource_mut::<Events<RelationErrorEvent<Acquaintance>>>()
+            .drain()
+            .collect::<Vec<_>>(),
+        vec![RelationErrorEvent::new(RelationError::Dangling {
+            entity: c,
+            dangling: a
+        })]
+    );
+
+    // Acquaintances are kept around after despawning, until pruned.
+    world.despawn(b);
+    world.flush();
+
+    assert_eq!(
+        world.get::<Acquaintances>(c),
+        Some(&Acquaintances::from_iter([b]))
+    );
+
+    assert_eq!(prune_dangling::<Acquaintance>(&mut world), 1);
+    world.flush();
+
+    assert_eq!(world.get::<Acquaintances>(c), None);
+    assert_eq!(
+        world
+            .resource_mut::<Events<RelationErrorEvent<Acquaintance>>>()
+            .drain()
+            .collect::<Vec<_>>(),
+        vec![RelationErrorEvent::new(RelationError::Dangling {
+            entity: c,
+            dangling: b
+        })]
+    );
+}
+
+#[test]
+fn edge_data() {
+    let mut world = World::new();
+    world.init_resource::<Events<RelationEvent<Fellowship>>>();
+
+    let a = world.spawn_empty().id();
+    let b = world.spawn_empty().id();
+    let c = world.spawn_empty().id();
+    world
+        .entity_mut(a)
+        .relate_with::<FellowOf>(b, FellowsSince(2020));
+    world.flush();
+    world.entity_mut(a).relate::<FellowOf>(c);
+    world.flush();
+
+    // The payload is shared by both sides of the edge.
+    assert_eq!(
+        world.get::<Fellows>(b).unwrap().data(a),
+        Some(&FellowsSince(2020))
+    );
+    assert_eq!(
+        world
+            .get::<Fellows>(a)
+            .unwrap()
+            .iter_with_data()
+            .collect::<Vec<_>>(),
+        vec![(b, Some(&FellowsSince(2020))), (c, None)]
+    );
+
+    world
+        .entity_mut(c)
+        .modify_related::<FellowOf>(move |fellows| {
+            fellows.set_data(a, FellowsSince(2024));
+        });
+    world.flush();
+
+    assert_eq!(
+        world.get::<Fellows>(a).unwrap().data(c),
+        Some(&FellowsSince(2024))
+    );
+
+    world.entity_mut(b).unrelate::<FellowOf>(a);
+    world.flush();
+
+    assert_eq!(world.get::<Fellows>(a).unwrap().data(b), None);
+    assert_eq!(
+        world
+            .resource_mut::<Events<RelationEvent<Fellowship>>>()
+            .drain()
+            .collect::<Vec<_>>(),
+        vec![
+            RelationEvent::added(a, b, RelationCause::Insert)
+                .with_data(Some(Arc::new(FellowsSince(2020)))),
+            RelationEvent::added(a, c, RelationCause::Mutation),
+            RelationEvent::removed(b, a, RelationCause::Mutation)
+                .with_data(Some(Arc::new(FellowsSince(2020)))),
+        ]
+    );
+}
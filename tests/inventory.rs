@@ -0,0 +1,355 @@
+use bevy_ecs::{component::Component, entity::Entity, event::Events, world::World};
+use evergreen_relations::{
+    container::{BoundedEntities, NamedSlots},
+    error::RelationError,
+    event::RelationErrorEvent,
+    prelude::*,
+};
+
+/// A directed 1:N relationship with at most 4 items equipped at a time,
+/// evicting the oldest one when full.
+#[derive(Relation)]
+#[relation(source = EquippedBy, target = Equips)]
+pub struct Equipment;
+
+pub type Wearer = Related<EquippedBy>;
+
+#[derive(Relatable)]
+#[relatable(Option<Entity> in Equipment, opposite = Equips)]
+pub struct EquippedBy;
+
+pub type Equipped = Related<Equips>;
+
+#[derive(Relatable)]
+#[relatable(BoundedEntities<4> in Equipment, opposite = EquippedBy)]
+pub struct Equips;
+
+/// A directed 1:N relationship with at most 6 passengers, refusing any more.
+#[derive(Relation)]
+#[relation(source = SeatedIn, target = Seats)]
+pub struct Seating;
+
+pub type Vehicle = Related<SeatedIn>;
+
+#[derive(Relatable)]
+#[relatable(Option<Entity> in Seating, opposite = Seats)]
+pub struct SeatedIn;
+
+pub type Passengers = Related<Seats>;
+
+#[derive(Relatable)]
+#[relatable(BoundedEntities<6> in Seating, opposite = SeatedIn, on_conflict = reject)]
+pub struct Seats;
+
+/// A directed 1:N relationship with at most 2 slotted items, evicting the
+/// item with the lowest priority when full.
+#[derive(Relation)]
+#[relation(source = SlottedIn, target = Slots)]
+pub struct Hotbar;
+
+pub type Slot = Related<SlottedIn>;
+
+#[derive(Relatable)]
+#[relatable(Option<Entity> in Hotbar, opposite = Slots)]
+pub struct SlottedIn;
+
+pub type Slotted = Related<Slots>;
+
+#[derive(Relatable)]
+#[relatable(BoundedEntities<2> in Hotbar, opposite = SlottedIn, on_conflict = evict(lowest_priority))]
+pub struct Slots;
+
+#[derive(Component)]
+pub struct Priority(u32);
+
+fn lowest_priority(world: &World, _: Entity, items: &[Entity]) -> Entity {
+    items
+        .iter()
+        .copied()
+        .min_by_key(|&item| world.get::<Priority>(item).map_or(0, |priority| priority.0))
+        .unwrap()
+}
+
+/// A directed 1:N relationship between items and the body slots they are
+/// worn on.
+#[derive(Relation)]
+#[relation(source = WornOn, target = Wears, data = BodySlot)]
+pub struct Loadout;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BodySlot {
+    #[default]
+    Head,
+    LeftHand,
+    RightHand,
+}
+
+pub type Wearing = Related<WornOn>;
+
+#[derive(Relatable)]
+#[relatable(Option<Entity> in Loadout, opposite = Wears)]
+pub struct WornOn;
+
+pub type Worn = Related<Wears>;
+
+#[derive(Relatable)]
+#[relatable(NamedSlots<BodySlot> in Loadout, opposite = WornOn)]
+pub struct Wears;
+
+fn related<N: Relatable>(world: &World, entity: Entity) -> Vec<Entity> {
+    world
+        .get::<Related<N>>(entity)
+        .map(|related| related.iter().collect())
+        .unwrap_or_default()
+}
+
+#[test]
+fn evict_oldest() {
+    let mut world = World::new();
+    world.init_resource::<Events<RelationEvent<Equipment>>>();
+
+    let a = world.spawn_empty().id();
+    let items = (0..5)
+        .map(|_| world.spawn(Wearer::new(a)).id())
+        .collect::<Vec<_>>();
+    world.flush();
+
+    assert_eq!(related::<Equips>(&world, a), items[1..].to_vec());
+    assert_eq!(world.get::<Wearer>(items[0]), None);
+    assert!(world
+        .resource_mut::<Events<RelationEvent<Equipment>>>()
+        .drain()
+        .any(|event| event == RelationEvent::removed(items[0], a, RelationCause::Steal)));
+}
+
+#[test]
+fn reject() {
+    let mut world = World::new();
+    world.init_resource::<Events<RelationErrorEvent<Seating>>>();
+
+    let a = world.spawn_empty().id();
+    let passengers = (0..7)
+        .map(|_| world.spawn(Vehicle::new(a)).id())
+        .collect::<Vec<_>>();
+    world.flush();
+
+    // The last passenger is refused, and no longer points at the full vehicle.
+    assert_eq!(related::<Seats>(&world, a), passengers[..6].to_vec());
+    assert_eq!(world.get::<Vehicle>(passengers[6]), None);
+    assert_eq!(
+        world
+            .resource_mut::<Events<RelationErrorEvent<Seating>>>()
+            .drain()
+            .collect::<Vec<_>>(),
+        vec![RelationErrorEvent::new(RelationError::Rejected {
+            entity: passengers[6],
+            target: a,
+        })]
+    );
+}
+
+#[test]
+fn evict_chosen() {
+    let mut world = World::new();
+
+    let a = world.spawn_empty().id();
+    let b = world.spawn((Slot::new(a), Priority(2))).id();
+    let c = world.spawn((Slot::new(a), Priority(1))).id();
+    let d = world.spawn((Slot::new(a), Priority(3))).id();
+    world.flush();
+
+    assert_eq!(related::<Slots>(&world, a), vec![b, d]);
+    assert_eq!(world.get::<Slot>(c), None);
+}
+
+#[test]
+fn reject_own_side() {
+    let mut world = World::new();
+    world.init_resource::<Events<RelationErrorEvent<Seating>>>();
+
+    let a = world.spawn_empty().id();
+    let passengers = (0..6)
+        .map(|_| world.spawn(Vehicle::new(a)).id())
+        .collect::<Vec<_>>();
+    world.flush();
+
+    // Relating from the full vehicle itself is refused the same way.
+    let p = world.spawn_empty().id();
+    world.entity_mut(a).relate::<Seats>(p);
+    world.flush();
+
+    assert_eq!(related::<Seats>(&world, a), passengers);
+    assert_eq!(world.get::<Vehicle>(p), None);
+    assert_eq!(
+        world
+            .resource_mut::<Events<RelationErrorEvent<Seating>>>()
+            .drain()
+            .collect::<Vec<_>>(),
+        vec![RelationErrorEvent::new(RelationError::Rejected {
+            entity: p,
+            target: a,
+        })]
+    );
+    assert_eq!(
+        world.entity_mut(p).try_relate::<SeatedIn>(a).err(),
+        Some(RelationError::Rejected {
+            entity: p,
+            target: a
+        })
+    );
+    assert_eq!(
+        world.entity_mut(a).try_relate::<Seats>(p).err(),
+        Some(RelationError::Rejected {
+            entity: p,
+            target: a
+        })
+    );
+}
+
+#[test]
+fn steal_own_side() {
+    let mut world = World::new();
+    world.init_resource::<Events<RelationEvent<Equipment>>>();
+
+    let a = world.spawn_empty().id();
+    let items = (0..4)
+        .map(|_| world.spawn(Wearer::new(a)).id())
+        .collect::<Vec<_>>();
+    world.flush();
+    world
+        .resource_mut::<Events<RelationEvent<Equipment>>>()
+        .clear();
+
+    // Relating from the full wearer itself evicts the oldest item the same way.
+    let e = world.spawn_empty().id();
+    world.entity_mut(a).relate::<Equips>(e);
+    world.flush();
+
+    assert_eq!(related::<Equips>(&world, a), [&items[1..], &[e]].concat());
+    assert_eq!(world.get::<Wearer>(items[0]), None);
+    assert_eq!(
+        world
+            .resource_mut::<Events<RelationEvent<Equipment>>>()
+            .drain()
+            .collect::<Vec<_>>(),
+        vec![
+            RelationEvent::removed(items[0], a, RelationCause::Steal),
+            RelationEvent::added(e, a, RelationCause::Mutation),
+        ]
+    );
+}
+
+#[test]
+fn evict_chosen_own_side() {
+    let mut world = World::new();
+    world.init_resource::<Events<RelationEvent<Hotbar>>>();
+
+    let a = world.spawn_empty().id();
+    let b = world.spawn((Slot::new(a), Priority(1))).id();
+    let c = world.spawn((Slot::new(a), Priority(2))).id();
+    world.flush();
+
+    let d = world.spawn(Priority(3)).id();
+    world.entity_mut(a).relate::<Slots>(d);
+    world.flush();
+
+    assert_eq!(related::<Slots>(&world, a), vec![c, d]);
+    assert_eq!(world.get::<Slot>(b), None);
+    assert_eq!(world.get::<Slot>(d), Some(&Slot::new(a)));
+    assert!(world
+        .resource_mut::<Events<RelationEvent<Hotbar>>>()
+        .drain()
+        .any(|event| event == RelationEvent::removed(b, a, RelationCause::Steal)));
+}
+
+#[test]
+fn named_slots() {
+    let mut world = World::new();
+
+    let helmet = world.spawn_empty().id();
+    let shield = world.spawn_empty().id();
+    let sword = world.spawn_empty().id();
+    let a = world.spawn(Worn::with_slot(BodySlot::Head, helmet)).id();
+    world.entity_mut(a).modify_related::<Wears>(move |worn| {
+        worn.set_slot(BodySlot::LeftHand, shield);
+        worn.set_slot(BodySlot::RightHand, sword);
+    });
+    world.flush();
+
+    let worn = world.get::<Worn>(a).unwrap();
+    assert_eq!(worn.get_slot(BodySlot::LeftHand), Some(shield));
+    assert_eq!(worn.slot_of(sword), Some(BodySlot::RightHand));
+
+    // Each item knows which slot it occupies.
+    for (item, slot) in [
+        (helmet, BodySlot::Head),
+        (shield, BodySlot::LeftHand),
+        (sword, BodySlot::RightHand),
+    ] {
+        assert_eq!(world.get::<Wearing>(item).unwrap().get(), Some(a));
+        assert_eq!(world.get::<Wearing>(item).unwrap().data(a), Some(&slot));
+    }
+
+    // Moving the sword to the left hand displaces the shield.
+    world.entity_mut(a).modify_related::<Wears>(move |worn| {
+        worn.set_slot(BodySlot::LeftHand, sword);
+        worn.clear_slot(BodySlot::Head);
+    });
+    world.flush();
+
+    assert_eq!(
+        world
+            .get::<Worn>(a)
+            .unwrap()
+            .iter_slots()
+            .collect::<Vec<_>>(),
+        vec![(BodySlot::LeftHand, sword)]
+    );
+    assert_eq!(
+        world.get::<Wearing>(sword).unwrap().data(a),
+        Some(&BodySlot::LeftHand)
+    );
+    assert_eq!(world.get::<Wearing>(shield), None);
+    assert_eq!(world.get::<Wearing>(helmet), None);
+}
+
+#[test]
+fn named_slots_without_slot() {
+    let mut world = World::new();
+    world.init_resource::<Events<RelationErrorEvent<Loadout>>>();
+
+    let a = world.spawn_empty().id();
+    let helmet = world.spawn_empty().id();
+    let sword = world.spawn_empty().id();
+    world.entity_mut(helmet).relate::<WornOn>(a);
+    world.entity_mut(a).relate::<Wears>(sword);
+    world.flush();
+
+    // Neither side says which slot to use, so both are refused.
+    assert_eq!(world.get::<Worn>(a), None);
+    assert_eq!(world.get::<Wearing>(helmet), None);
+    assert_eq!(world.get::<Wearing>(sword), None);
+    assert_eq!(
+        world
+            .resource_mut::<Events<RelationErrorEvent<Loadout>>>()
+            .drain()
+            .collect::<Vec<_>>(),
+        vec![
+            RelationErrorEvent::new(RelationError::NoSlot {
+                entity: sword,
+                target: a,
+            }),
+            RelationErrorEvent::new(RelationError::NoSlot {
+                entity: helmet,
+                target: a,
+            }),
+        ]
+    );
+    assert_eq!(
+        world.entity_mut(helmet).try_relate::<WornOn>(a).err(),
+        Some(RelationError::NoSlot {
+            entity: helmet,
+            target: a,
+        })
+    );
+}
@@ -0,0 +1,95 @@
+use bevy_ecs::{entity::Entity, event::Events, world::World};
+use evergreen_relations::{container::OrderedEntities, event::RelationReordered, prelude::*};
+
+/// A directed 1:N relationship between UI nodes, where the order of the
+/// children matters.
+#[derive(Relation)]
+#[relation(source = ContainedBy, target = Contains)]
+pub struct Layout;
+
+pub type Container = Related<ContainedBy>;
+
+#[derive(Relatable)]
+#[relatable(Entity in Layout, opposite = Contains)]
+pub struct ContainedBy;
+
+pub type Contents = Related<Contains>;
+
+#[derive(Relatable)]
+#[relatable(OrderedEntities in Layout, opposite = ContainedBy)]
+pub struct Contains;
+
+fn contents(world: &World, entity: Entity) -> Vec<Entity> {
+    world
+        .get::<Contents>(entity)
+        .map(|contents| contents.iter().collect())
+        .unwrap_or_default()
+}
+
+#[test]
+fn reorder() {
+    let mut world = World::new();
+    world.init_resource::<Events<RelationEvent<Layout>>>();
+    world.init_resource::<Events<RelationReordered<Contains>>>();
+
+    let a = world.spawn_empty().id();
+    let b = world.spawn(Container::new(a)).id();
+    let c = world.spawn(Container::new(a)).id();
+    let d = world.spawn(Container::new(a)).id();
+    world.flush();
+
+    assert_eq!(contents(&world, a), vec![b, c, d]);
+    world
+        .resource_mut::<Events<RelationEvent<Layout>>>()
+        .clear();
+
+    world
+        .entity_mut(a)
+        .modify_related::<Contains>(move |contents| {
+            assert!(contents.move_to(d, 0));
+            assert!(contents.swap(b, c));
+        });
+    world.flush();
+
+    assert_eq!(contents(&world, a), vec![d, c, b]);
+    assert_eq!(world.get::<Contents>(a).unwrap().index_of(b), Some(2));
+
+    // Reordering is reported on its own, without removing and re-adding.
+    assert!(world
+        .resource_mut::<Events<RelationEvent<Layout>>>()
+        .is_empty());
+    assert_eq!(
+        world
+            .resource_mut::<Events<RelationReordered<Contains>>>()
+            .drain()
+            .collect::<Vec<_>>(),
+        vec![RelationReordered::new(a)]
+    );
+
+    // Inserting at an index relates the new entity like any other.
+    let e = world.spawn_empty().id();
+    world
+        .entity_mut(a)
+        .modify_related::<Contains>(move |contents| contents.insert_at(1, e));
+    world.flush();
+
+    assert_eq!(contents(&world, a), vec![d, e, c, b]);
+    assert_eq!(world.get::<Container>(e).and_then(Container::get), Some(a));
+    assert!(world
+        .resource_mut::<Events<RelationReordered<Contains>>>()
+        .is_empty());
+
+    world
+        .entity_mut(a)
+        .modify_related::<Contains>(move |contents| contents.sort_by_key(|id| id.index()));
+    world.flush();
+
+    assert_eq!(contents(&world, a), vec![b, c, d, e]);
+    assert_eq!(
+        world
+            .resource_mut::<Events<RelationReordered<Contains>>>()
+            .drain()
+            .count(),
+        1
+    );
+}
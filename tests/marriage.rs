@@ -1,9 +1,10 @@
-use std::marker::PhantomData;
-
-use bevy_ecs::{entity::Entity, event::Events, world::World};
+use bevy_ecs::{component::Component, entity::Entity, event::Events, world::World};
 
 use evergreen_relations::{
-    event::RelationEvent,
+    coalesce::{flush_relation_events, CoalescedRelationEvents, RelationBatch},
+    commands::{RelationCommands, TryRelationCommands},
+    error::{RelationError, RelationErrorHandler},
+    event::{RelationCause, RelationErrorEvent, RelationEvent},
     related::Related,
     relation::{Relatable, Relation},
 };
@@ -19,6 +20,40 @@ pub type SignificantOther = Related<SignificantOtherOf>;
 #[relatable(Entity in Marriage, opposite = SignificantOtherOf)]
 pub struct SignificantOtherOf;
 
+/// An undirected 1:1 relationship that leaves a mark on the survivor.
+#[derive(Relation)]
+#[relation(source = WedTo, target = WedTo)]
+pub struct Wedlock;
+
+pub type Spouse = Related<WedTo>;
+
+#[derive(Relatable)]
+#[relatable(Entity in Wedlock, opposite = WedTo, on_orphan = insert(Widowed))]
+pub struct WedTo;
+
+#[derive(Component)]
+pub struct Widowed;
+
+/// An undirected 1:1 relationship that refuses to be broken up by a third
+/// party, leaving them jilted.
+#[derive(Relation)]
+#[relation(source = BetrothedTo, target = BetrothedTo)]
+pub struct Betrothal;
+
+pub type Betrothed = Related<BetrothedTo>;
+
+#[derive(Relatable)]
+#[relatable(
+    Option<Entity> in Betrothal,
+    opposite = BetrothedTo,
+    on_conflict = reject,
+    on_orphan = insert(Jilted),
+)]
+pub struct BetrothedTo;
+
+#[derive(Component)]
+pub struct Jilted;
+
 #[test]
 fn add_remove() {
     let mut world = World::new();
@@ -194,7 +229,11 @@ fn events() {
             .resource_mut::<Events<RelationEvent<Marriage>>>()
             .drain()
             .collect::<Vec<_>>(),
-        vec![RelationEvent::<Marriage>::Added(b, a, PhantomData)]
+        vec![RelationEvent::<Marriage>::added(
+            b,
+            a,
+            RelationCause::Insert
+        )]
     );
 
     world.entity_mut(a).remove::<SignificantOther>();
@@ -208,6 +247,385 @@ fn events() {
             .resource_mut::<Events<RelationEvent<Marriage>>>()
             .drain()
             .collect::<Vec<_>>(),
-        vec![RelationEvent::<Marriage>::Removed(a, b, PhantomData)]
+        vec![RelationEvent::<Marriage>::removed(
+            a,
+            b,
+            RelationCause::Remove
+        )]
+    );
+}
+
+#[test]
+fn replace_then_remove_events() {
+    let mut world = World::new();
+
+    world.init_resource::<Events<RelationEvent<Marriage>>>();
+
+    let a = world.spawn_empty().id();
+    let b = world.spawn(SignificantOther::new(a)).id();
+    let c = world.spawn_empty().id();
+    world.flush();
+    world
+        .resource_mut::<Events<RelationEvent<Marriage>>>()
+        .clear();
+
+    // Both changes are applied before the work queued by their hooks.
+    world.commands().queue(move |world: &mut World| {
+        world.entity_mut(a).insert(SignificantOther::new(c));
+        world.entity_mut(a).remove::<SignificantOther>();
+    });
+    world.flush();
+
+    assert_eq!(world.get::<SignificantOther>(a), None);
+    assert_eq!(world.get::<SignificantOther>(b), None);
+    assert_eq!(world.get::<SignificantOther>(c), None);
+    assert_eq!(
+        world
+            .resource_mut::<Events<RelationEvent<Marriage>>>()
+            .drain()
+            .collect::<Vec<_>>(),
+        vec![RelationEvent::<Marriage>::removed(
+            a,
+            b,
+            RelationCause::Replace
+        )]
+    );
+}
+
+#[test]
+fn widowed() {
+    let mut world = World::new();
+
+    let a = world.spawn_empty().id();
+    let b = world.spawn(Spouse::new(a)).id();
+    world.flush();
+
+    assert!(!world.entity(a).contains::<Widowed>());
+    assert!(!world.entity(b).contains::<Widowed>());
+
+    world.despawn(b);
+    world.flush();
+
+    assert_eq!(world.get::<Spouse>(a), None);
+    assert!(world.entity(a).contains::<Widowed>());
+}
+
+#[test]
+fn widowed_dangling() {
+    let mut world = World::new();
+
+    let b = world.spawn_empty().id();
+    world.despawn(b);
+
+    // Marrying an entity that no longer exists prunes it, leaving a widowed.
+    let a = world.spawn(Spouse::new(b)).id();
+    world.flush();
+
+    assert_eq!(world.get::<Spouse>(a), None);
+    assert!(world.entity(a).contains::<Widowed>());
+}
+
+#[test]
+fn conflict_steal() {
+    let mut world = World::new();
+
+    world.init_resource::<Events<RelationEvent<Marriage>>>();
+
+    let a = world.spawn_empty().id();
+    let b = world.spawn(SignificantOther::new(a)).id();
+    world.flush();
+    world
+        .resource_mut::<Events<RelationEvent<Marriage>>>()
+        .clear();
+
+    let c = world.spawn(SignificantOther::new(a)).id();
+    world.flush();
+
+    assert_eq!(
+        world.get::<SignificantOther>(a),
+        Some(&SignificantOther::new(c))
+    );
+    assert_eq!(world.get::<SignificantOther>(b), None);
+    assert_eq!(
+        world.get::<SignificantOther>(c),
+        Some(&SignificantOther::new(a))
+    );
+    assert_eq!(
+        world
+            .resource_mut::<Events<RelationEvent<Marriage>>>()
+            .drain()
+            .collect::<Vec<_>>(),
+        vec![
+            RelationEvent::<Marriage>::added(c, a, RelationCause::Insert),
+            RelationEvent::<Marriage>::removed(a, b, RelationCause::Steal),
+        ]
     );
 }
+
+#[test]
+fn conflict_reject() {
+    let mut world = World::new();
+
+    world.init_resource::<Events<RelationErrorEvent<Betrothal>>>();
+
+    let a = world.spawn_empty().id();
+    let b = world.spawn(Betrothed::new(a)).id();
+    world.flush();
+
+    let c = world.spawn(Betrothed::new(a)).id();
+    world.flush();
+
+    assert_eq!(world.get::<Betrothed>(a), Some(&Betrothed::new(b)));
+    assert_eq!(world.get::<Betrothed>(b), Some(&Betrothed::new(a)));
+    assert_eq!(world.get::<Betrothed>(c), None);
+    assert!(world.entity(c).contains::<Jilted>());
+    assert!(!world.entity(b).contains::<Jilted>());
+    assert_eq!(
+        world
+            .resource_mut::<Events<RelationErrorEvent<Betrothal>>>()
+            .drain()
+            .collect::<Vec<_>>(),
+        vec![RelationErrorEvent::new(RelationError::Rejected {
+            entity: c,
+            target: a
+        })]
+    );
+}
+
+#[test]
+fn conflict_same_batch() {
+    let mut world = World::new();
+
+    let a = world.spawn_empty().id();
+    let b = world.spawn_empty().id();
+    let c = world.spawn_empty().id();
+
+    // Both endpoints are written with contradictory data; the last write wins.
+    world.commands().entity(a).insert(SignificantOther::new(b));
+    world.commands().entity(b).insert(SignificantOther::new(c));
+    world.flush();
+
+    assert_eq!(world.get::<SignificantOther>(a), None);
+    assert_eq!(
+        world.get::<SignificantOther>(b),
+        Some(&SignificantOther::new(c))
+    );
+    assert_eq!(
+        world.get::<SignificantOther>(c),
+        Some(&SignificantOther::new(b))
+    );
+}
+
+#[test]
+fn try_relate() {
+    let mut world = World::new();
+
+    let a = world.spawn_empty().id();
+    let b = world.spawn_empty().id();
+    let c = world.spawn_empty().id();
+    let d = world.spawn_empty().id();
+    world.despawn(d);
+
+    assert!(world.entity_mut(a).try_relate::<BetrothedTo>(b).is_ok());
+    world.flush();
+
+    assert_eq!(
+        world.entity_mut(c).try_relate::<BetrothedTo>(a).err(),
+        Some(RelationError::Rejected {
+            entity: c,
+            target: a
+        })
+    );
+    assert_eq!(
+        world.entity_mut(c).try_relate::<BetrothedTo>(d).err(),
+        Some(RelationError::NoSuchEntity(d))
+    );
+    assert_eq!(
+        world.entity_mut(c).try_unrelate::<BetrothedTo>(a).err(),
+        Some(RelationError::NotRelated {
+            entity: c,
+            other: a
+        })
+    );
+    world.flush();
+
+    assert_eq!(world.get::<Betrothed>(a), Some(&Betrothed::new(b)));
+    assert_eq!(world.get::<Betrothed>(b), Some(&Betrothed::new(a)));
+    assert_eq!(world.get::<Betrothed>(c), None);
+}
+
+#[test]
+#[should_panic = "relation error"]
+fn error_handler_panic() {
+    let mut world = World::new();
+    world.insert_resource(RelationErrorHandler::Panic);
+
+    let a = world.spawn_empty().id();
+    let b = world.spawn(Betrothed::new(a)).id();
+    world.flush();
+
+    world.commands().entity(b).despawn();
+    world.commands().entity(b).relate::<SignificantOtherOf>(a);
+    world.flush();
+}
+
+#[test]
+fn coalesced_events() {
+    let mut world = World::new();
+
+    world.init_resource::<Events<RelationEvent<Marriage>>>();
+    world.init_resource::<Events<RelationBatch<Marriage>>>();
+    world.init_resource::<CoalescedRelationEvents<Marriage>>();
+
+    let a = world.spawn_empty().id();
+    let b = world.spawn_empty().id();
+    let c = world.spawn_empty().id();
+
+    for _ in 0..10_000 {
+        world.entity_mut(a).insert(SignificantOther::new(b));
+        world.entity_mut(a).remove::<SignificantOther>();
+    }
+    world.entity_mut(a).insert(SignificantOther::new(c));
+    world.flush();
+
+    flush_relation_events::<Marriage>(&mut world);
+
+    assert_eq!(
+        world
+            .resource_mut::<Events<RelationEvent<Marriage>>>()
+            .drain()
+            .collect::<Vec<_>>(),
+        vec![RelationEvent::<Marriage>::added(
+            a,
+            c,
+            RelationCause::Insert
+        )]
+    );
+    assert_eq!(
+        world
+            .resource_mut::<Events<RelationBatch<Marriage>>>()
+            .drain()
+            .collect::<Vec<_>>(),
+        vec![RelationBatch::new(vec![(a, c)], vec![])]
+    );
+
+    // Removing and re-adding the same edge cancels out as well.
+    world.entity_mut(c).remove::<SignificantOther>();
+    world.entity_mut(c).insert(SignificantOther::new(a));
+    world.flush();
+
+    flush_relation_events::<Marriage>(&mut world);
+
+    assert!(world
+        .resource::<Events<RelationEvent<Marriage>>>()
+        .is_empty());
+    assert!(world
+        .resource::<Events<RelationBatch<Marriage>>>()
+        .is_empty());
+}
+
+#[test]
+fn coalesced_events_wait_for_flush() {
+    let mut world = World::new();
+
+    world.init_resource::<Events<RelationEvent<Marriage>>>();
+    world.init_resource::<CoalescedRelationEvents<Marriage>>();
+
+    let a = world.spawn_empty().id();
+    let b = world.spawn(SignificantOther::new(a)).id();
+    world.flush();
+
+    // Applying the commands doesn't send the buffered changes.
+    assert!(world
+        .resource::<Events<RelationEvent<Marriage>>>()
+        .is_empty());
+    assert!(!world
+        .resource::<CoalescedRelationEvents<Marriage>>()
+        .is_empty());
+
+    flush_relation_events::<Marriage>(&mut world);
+
+    assert_eq!(
+        world
+            .resource_mut::<Events<RelationEvent<Marriage>>>()
+            .drain()
+            .collect::<Vec<_>>(),
+        vec![RelationEvent::<Marriage>::added(
+            b,
+            a,
+            RelationCause::Insert
+        )]
+    );
+    assert!(world
+        .resource::<CoalescedRelationEvents<Marriage>>()
+        .is_empty());
+}
+
+#[test]
+#[should_panic = "cannot relate to Entity::PLACEHOLDER"]
+fn placeholder() {
+    let _ = Betrothed::new(Entity::PLACEHOLDER);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic = "cannot relate to Entity::PLACEHOLDER"]
+fn push_placeholder() {
+    let mut betrothed = Betrothed::new(Entity::from_raw(1));
+    betrothed.push(Entity::PLACEHOLDER);
+}
+
+#[test]
+fn relate_placeholder() {
+    let mut world = World::new();
+    world.init_resource::<Events<RelationErrorEvent<Marriage>>>();
+
+    let a = world.spawn_empty().id();
+    let b = world.spawn(SignificantOther::new(a)).id();
+    world.flush();
+
+    // Relating to the placeholder is refused instead of unrelating.
+    world
+        .entity_mut(b)
+        .relate::<SignificantOtherOf>(Entity::PLACEHOLDER);
+    world.flush();
+
+    assert_eq!(
+        world.get::<SignificantOther>(b),
+        Some(&SignificantOther::new(a))
+    );
+    assert_eq!(
+        world
+            .resource_mut::<Events<RelationErrorEvent<Marriage>>>()
+            .drain()
+            .collect::<Vec<_>>(),
+        vec![RelationErrorEvent::new(RelationError::Placeholder(b))]
+    );
+    assert_eq!(
+        world
+            .entity_mut(b)
+            .try_relate::<SignificantOtherOf>(Entity::PLACEHOLDER)
+            .err(),
+        Some(RelationError::Placeholder(b))
+    );
+}
+
+#[test]
+fn single_entity() {
+    let mut world = World::new();
+
+    let a = world.spawn_empty().id();
+    let b = world.spawn(Betrothed::new(a)).id();
+    world.flush();
+
+    assert_eq!(world.get::<Betrothed>(a).and_then(Betrothed::get), Some(b));
+    assert_eq!(world.get::<Betrothed>(b).map(Betrothed::len), Some(1));
+
+    world
+        .entity_mut(a)
+        .modify_related::<BetrothedTo>(|betrothed| betrothed.clear());
+    world.flush();
+
+    assert_eq!(world.get::<Betrothed>(a), None);
+    assert_eq!(world.get::<Betrothed>(b), None);
+}
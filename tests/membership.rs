@@ -0,0 +1,64 @@
+use bevy_ecs::{entity::Entity, world::World};
+use evergreen_relations::{container::IndexedEntities, prelude::*};
+
+/// A directed 1:N relationship between a guild and its many members.
+#[derive(Relation)]
+#[relation(source = MemberOf, target = HasMember)]
+pub struct Membership;
+
+pub type Guild = Related<MemberOf>;
+
+#[derive(Relatable)]
+#[relatable(Entity in Membership, opposite = HasMember)]
+pub struct MemberOf;
+
+pub type Members = Related<HasMember>;
+
+#[derive(Relatable)]
+#[relatable(IndexedEntities in Membership, opposite = MemberOf)]
+pub struct HasMember;
+
+#[test]
+fn insertion_order() {
+    let mut world = World::new();
+
+    let a = world.spawn_empty().id();
+    let members = (0..5)
+        .map(|_| world.spawn(Guild::new(a)).id())
+        .collect::<Vec<_>>();
+    world.flush();
+
+    world.despawn(members[1]);
+    world.despawn(members[3]);
+    world.entity_mut(a).relate::<HasMember>(members[0]);
+    world.flush();
+
+    assert_eq!(
+        world.get::<Members>(a).unwrap().iter().collect::<Vec<_>>(),
+        vec![members[0], members[2], members[4]]
+    );
+    assert_eq!(
+        world.get::<Members>(a),
+        Some(&Members::from_iter([members[0], members[2], members[4]]))
+    );
+}
+
+#[test]
+fn despawn_many() {
+    let mut world = World::new();
+
+    let a = world.spawn_empty().id();
+    let members = (0..100_000)
+        .map(|_| world.spawn(Guild::new(a)).id())
+        .collect::<Vec<_>>();
+    world.flush();
+
+    assert_eq!(world.get::<Members>(a).map(Members::len), Some(100_000));
+
+    for member in members {
+        world.despawn(member);
+    }
+    world.flush();
+
+    assert_eq!(world.get::<Members>(a), None);
+}
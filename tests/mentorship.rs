@@ -0,0 +1,212 @@
+use bevy_ecs::{entity::Entity, world::World};
+use std::collections::BTreeSet;
+
+use evergreen_relations::{
+    container::{
+        BoundedEntities, EntityBitSet, EntityContainer, IndexedEntities, MultiEntities, NamedSlots,
+        OrderedEntities,
+    },
+    prelude::*,
+};
+use smallvec::SmallVec;
+
+/// A directed N:M relationship between mentors and their mentees.
+#[derive(Relation)]
+#[relation(source = MentorOf, target = MenteeOf)]
+pub struct Mentorship;
+
+pub type Mentees = Related<MentorOf>;
+
+#[derive(Relatable)]
+#[relatable(Roster in Mentorship, opposite = MenteeOf)]
+pub struct MentorOf;
+
+pub type Mentors = Related<MenteeOf>;
+
+#[derive(Relatable)]
+#[relatable(Roster in Mentorship, opposite = MentorOf)]
+pub struct MenteeOf;
+
+/// A user-defined container that only implements the required methods.
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct Roster(Vec<Entity>);
+
+impl EntityContainer for Roster {
+    fn new(entity: Entity) -> Self {
+        Self(vec![entity])
+    }
+
+    fn empty() -> Self {
+        Self(Vec::new())
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn contains(&self, entity: Entity) -> bool {
+        self.0.as_slice().contains(&entity)
+    }
+
+    fn push(&mut self, entity: Entity) {
+        if !self.contains(entity) {
+            self.0.push(entity);
+        }
+    }
+
+    fn remove(&mut self, entity: Entity) {
+        self.0.retain(|&id| id != entity);
+    }
+
+    fn into_iter(self) -> impl Iterator<Item = Entity> {
+        IntoIterator::into_iter(self.0)
+    }
+
+    fn iter(&self) -> impl Iterator<Item = Entity> {
+        self.0.as_slice().iter().copied()
+    }
+}
+
+impl FromIterator<Entity> for Roster {
+    fn from_iter<T: IntoIterator<Item = Entity>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+#[test]
+fn default_methods() {
+    let a = Entity::from_raw(1);
+    let b = Entity::from_raw(2);
+    let c = Entity::from_raw(3);
+
+    let mut roster = Roster::with_capacity(3);
+    assert!(EntityContainer::is_empty(&roster));
+
+    roster.extend([a, b, c, a]);
+    assert_eq!(roster, Roster(vec![a, b, c]));
+    assert_eq!(EntityContainer::len(&roster), 3);
+
+    roster.retain(|id| id != b);
+    assert_eq!(roster, Roster(vec![a, c]));
+
+    roster.clear();
+    assert_eq!(roster, Roster::empty());
+}
+
+#[test]
+fn bulk_edits() {
+    let mut world = World::new();
+
+    let a = world.spawn_empty().id();
+    let b = world.spawn_empty().id();
+    let c = world.spawn_empty().id();
+    let mentor = world.spawn_empty().id();
+    world
+        .entity_mut(mentor)
+        .set_related::<MentorOf>([a, b, c, b]);
+    world.flush();
+
+    assert_eq!(world.get::<Mentees>(mentor).map(Mentees::len), Some(3));
+    assert_eq!(world.get::<Mentors>(b), Some(&Mentors::from_iter([mentor])));
+
+    world
+        .entity_mut(mentor)
+        .modify_related::<MentorOf>(move |mentees| mentees.retain(|id| id == b));
+    world.flush();
+
+    assert_eq!(world.get::<Mentees>(mentor), Some(&Mentees::from_iter([b])));
+    assert_eq!(world.get::<Mentors>(a), None);
+    assert_eq!(world.get::<Mentors>(c), None);
+
+    world
+        .entity_mut(mentor)
+        .modify_related::<MentorOf>(|mentees| mentees.clear());
+    world.flush();
+
+    assert_eq!(world.get::<Mentees>(mentor), None);
+    assert_eq!(world.get::<Mentors>(b), None);
+}
+
+#[test]
+fn dangling() {
+    let mut world = World::new();
+
+    let a = world.spawn_empty().id();
+    let b = world.spawn_empty().id();
+    let c = world.spawn_empty().id();
+    world.despawn(a);
+    world.despawn(c);
+    let mentor = world.spawn(Mentees::from_iter([a, b, c])).id();
+    world.flush();
+
+    // Every despawned mentee is pruned at once.
+    assert_eq!(world.get::<Mentees>(mentor), Some(&Mentees::from_iter([b])));
+    assert_eq!(world.get::<Mentors>(b), Some(&Mentors::from_iter([mentor])));
+}
+
+#[test]
+fn built_in_containers() {
+    fn check<C: EntityContainer>() {
+        let name = std::any::type_name::<C>();
+        let entities = (1..=6).map(Entity::from_raw).collect::<Vec<_>>();
+        let [a, b, c] = [entities[0], entities[1], entities[2]];
+
+        // Pushing an entity that is already present doesn't add it again.
+        let mut container = C::new(a);
+        container.push(a);
+        container.extend([b, a, b, c, c]);
+        assert_eq!(
+            container.iter().collect::<Vec<_>>(),
+            vec![a, b, c],
+            "{name}"
+        );
+
+        let mut container = C::empty();
+        container.extend(entities.clone());
+        container.retain(|id| id.index() % 2 == 0);
+
+        let kept = container.iter().collect::<Vec<_>>();
+        assert_eq!(kept, vec![entities[1], entities[3], entities[5]], "{name}");
+        assert_eq!(EntityContainer::len(&container), 3, "{name}");
+        assert!(container.contains(entities[3]), "{name}");
+        assert!(!container.contains(entities[2]), "{name}");
+
+        container.clear();
+        assert!(EntityContainer::is_empty(&container), "{name}");
+        assert_eq!(container.iter().count(), 0, "{name}");
+    }
+
+    check::<SmallVec<[Entity; 4]>>();
+    check::<Vec<Entity>>();
+    check::<BTreeSet<Entity>>();
+    check::<BoundedEntities<8>>();
+    check::<OrderedEntities>();
+    check::<IndexedEntities>();
+    check::<MultiEntities>();
+    check::<EntityBitSet>();
+}
+
+#[test]
+fn duplicate_pushes() {
+    let [a, b] = [1, 2].map(Entity::from_raw);
+
+    // Pushing entities without a slot leaves the named slots as they are.
+    let mut slots = NamedSlots::<&'static str>::empty();
+    slots.set("head", a);
+    slots.push(b);
+    slots.push(a);
+    assert_eq!(slots.iter_slots().collect::<Vec<_>>(), vec![("head", a)]);
+
+    // A full bounded container keeps its oldest entity when re-pushing one it holds.
+    let mut bounded = BoundedEntities::<2>::empty();
+    bounded.extend([a, a, a]);
+    assert_eq!(bounded.iter().collect::<Vec<_>>(), vec![a]);
+    bounded.extend([b, a]);
+    assert_eq!(bounded.iter().collect::<Vec<_>>(), vec![a, b]);
+
+    // Multigraphs count every push instead.
+    let mut multi = MultiEntities::new(a);
+    multi.extend([a, b]);
+    assert_eq!(multi.multiplicity(a), 2);
+    assert_eq!(multi.multiplicity(b), 1);
+}
@@ -0,0 +1,53 @@
+use std::collections::BTreeSet;
+
+use bevy_ecs::{entity::Entity, world::World};
+use evergreen_relations::prelude::*;
+
+/// An undirected N:M relationship between entities, iterated in a stable order.
+#[derive(Relation)]
+#[relation(source = RivalOf, target = RivalOf)]
+pub struct Rivalry;
+
+pub type Rivals = Related<RivalOf>;
+
+#[derive(Relatable)]
+#[relatable(BTreeSet<Entity> in Rivalry, opposite = Self)]
+pub struct RivalOf;
+
+#[test]
+fn sorted() {
+    let mut world = World::new();
+
+    let entities = (0..6).map(|_| world.spawn_empty().id()).collect::<Vec<_>>();
+    let a = world
+        .spawn(Rivals::from_iter([
+            entities[4],
+            entities[1],
+            entities[3],
+            entities[1],
+        ]))
+        .id();
+    world.entity_mut(a).relate::<RivalOf>(entities[0]);
+    world.flush();
+
+    let rivals = world.get::<Rivals>(a).unwrap();
+    assert_eq!(
+        rivals.iter().collect::<Vec<_>>(),
+        vec![entities[0], entities[1], entities[3], entities[4]]
+    );
+    assert_eq!(
+        rivals.range(entities[1]..entities[4]).collect::<Vec<_>>(),
+        vec![entities[1], entities[3]]
+    );
+    assert_eq!(rivals.first(), Some(entities[0]));
+    assert_eq!(rivals.last(), Some(entities[4]));
+
+    for &rival in &[entities[0], entities[1], entities[3], entities[4]] {
+        assert_eq!(
+            world
+                .get::<Rivals>(rival)
+                .map(|rivals| rivals.iter().collect()),
+            Some(vec![a])
+        );
+    }
+}
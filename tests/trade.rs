@@ -0,0 +1,170 @@
+use bevy_ecs::{
+    component::Component,
+    entity::Entity,
+    observer::Trigger,
+    system::{Query, ResMut, Resource},
+    world::{OnAdd, World},
+};
+use evergreen_relations::{integrity::repair, prelude::*, relation::Side};
+
+/// A directed N:M relationship between factions, where each trade route is an
+/// entity of its own.
+#[derive(Relation)]
+#[relation(source = ExportsTo, target = ImportsFrom, reified)]
+pub struct Trade;
+
+pub type Exports = Related<ExportsTo>;
+
+#[derive(Relatable)]
+#[relatable(Vec<Entity> in Trade, opposite = ImportsFrom)]
+pub struct ExportsTo;
+
+pub type Imports = Related<ImportsFrom>;
+
+#[derive(Relatable)]
+#[relatable(Vec<Entity> in Trade, opposite = ExportsTo)]
+pub struct ImportsFrom;
+
+#[derive(Component, Debug, PartialEq)]
+pub struct Tariff(u32);
+
+fn edge(world: &World, source: Entity, target: Entity) -> Option<Entity> {
+    let edge = world.get::<Exports>(source)?.edge(target);
+    assert_eq!(edge, world.get::<Imports>(target)?.edge(source));
+    edge
+}
+
+#[test]
+fn edge_entity() {
+    let mut world = World::new();
+
+    let a = world.spawn_empty().id();
+    let b = world.spawn(Imports::new(vec![a])).id();
+    let c = world.spawn_empty().id();
+    world.entity_mut(a).relate::<ExportsTo>(c);
+    world.flush();
+
+    let ab = edge(&world, a, b).unwrap();
+    let ac = edge(&world, a, c).unwrap();
+    assert_ne!(ab, ac);
+
+    // Edge entities don't take part in comparing the components.
+    assert_eq!(world.get::<Imports>(b), Some(&Imports::new(vec![a])));
+    assert_eq!(world.get::<Edge<Trade>>(ab).map(Edge::source), Some(a));
+    assert_eq!(world.get::<Edge<Trade>>(ab).map(Edge::target), Some(b));
+
+    // Edges can hold their own components, and be queried.
+    world.entity_mut(ab).insert(Tariff(10));
+    let mut query = world.query::<(&Edge<Trade>, &Tariff)>();
+    assert_eq!(
+        query
+            .iter(&world)
+            .map(|(edge, tariff)| (edge.source(), edge.target(), tariff))
+            .collect::<Vec<_>>(),
+        vec![(a, b, &Tariff(10))]
+    );
+
+    // Unrelating despawns the edge.
+    world.entity_mut(c).unrelate::<ImportsFrom>(a);
+    world.flush();
+
+    assert!(world.get_entity(ac).is_err());
+    assert_eq!(
+        world
+            .get::<Exports>(a)
+            .unwrap()
+            .iter_with_edges()
+            .collect::<Vec<_>>(),
+        vec![(b, Some(ab))]
+    );
+}
+
+#[test]
+fn despawn_edge() {
+    let mut world = World::new();
+
+    let a = world.spawn_empty().id();
+    let b = world.spawn(Imports::new(vec![a])).id();
+    world.flush();
+
+    world.despawn(edge(&world, a, b).unwrap());
+    world.flush();
+
+    assert_eq!(world.get::<Exports>(a), None);
+    assert_eq!(world.get::<Imports>(b), None);
+}
+
+#[test]
+fn despawn_endpoint() {
+    let mut world = World::new();
+
+    let a = world.spawn_empty().id();
+    let b = world.spawn(Imports::new(vec![a])).id();
+    let c = world.spawn(Imports::new(vec![a])).id();
+    world.flush();
+
+    let ab = edge(&world, a, b).unwrap();
+    let ac = edge(&world, a, c).unwrap();
+
+    world.despawn(b);
+    world.flush();
+
+    assert!(world.get_entity(ab).is_err());
+    assert_eq!(edge(&world, a, c), Some(ac));
+    assert_eq!(world.get::<Exports>(a).unwrap().len(), 1);
+
+    // Replacing the component keeps the edges of the links that remain.
+    world.entity_mut(a).set_related::<ExportsTo>([c]);
+    world.flush();
+
+    assert_eq!(edge(&world, a, c), Some(ac));
+    assert_eq!(world.query::<&Edge<Trade>>().iter(&world).count(), 1);
+}
+
+#[test]
+fn repair_edge() {
+    let mut world = World::new();
+
+    let a = world.spawn_empty().id();
+    let b = world.spawn(Imports::new(vec![a])).id();
+    world.flush();
+
+    let ab = edge(&world, a, b).unwrap();
+
+    // Break the link on one side only, bypassing the hooks.
+    world.get_mut::<Imports>(b).unwrap().remove(a);
+    repair::<Trade>(&mut world, Side::Target);
+    world.flush();
+
+    assert_eq!(world.get::<Exports>(a), None);
+    assert!(world.get_entity(ab).is_err());
+}
+
+#[test]
+fn edge_spawned_once() {
+    #[derive(Resource, Default)]
+    struct Spawned(Vec<(Entity, Entity)>);
+
+    let mut world = World::new();
+    world.init_resource::<Spawned>();
+    world.add_observer(
+        |trigger: Trigger<OnAdd, Edge<Trade>>,
+         edges: Query<&Edge<Trade>>,
+         mut spawned: ResMut<Spawned>| {
+            let edge = edges.get(trigger.entity()).unwrap();
+            spawned.0.push((edge.source(), edge.target()));
+        },
+    );
+
+    let a = world.spawn_empty().id();
+    let b = world.spawn_empty().id();
+
+    // Both sides link up in the same flush, yet only one edge entity is ever made.
+    world.commands().entity(a).relate::<ExportsTo>(b);
+    world.commands().entity(b).relate::<ImportsFrom>(a);
+    world.flush();
+
+    assert_eq!(world.resource::<Spawned>().0, vec![(a, b)]);
+    assert!(edge(&world, a, b).is_some());
+    assert_eq!(world.query::<&Edge<Trade>>().iter(&world).count(), 1);
+}